@@ -3,13 +3,41 @@ CREATE TABLE links (
   user_id         BIGINT UNSIGNED NOT NULL,            -- 用户ID，外键
   short_code      VARCHAR(16)     DEFAULT NULL,        -- 允许为空
   long_url        TEXT            NOT NULL,
+  long_url_hash   CHAR(64)        DEFAULT NULL,        -- 规范化长 URL 的 SHA-256，用于去重
+  dedupe_hash     CHAR(64)        DEFAULT NULL,        -- 以去重方式创建时写入 long_url_hash，不再可复用时清空
   created_at      TIMESTAMP       NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  activate_at     DATETIME        NULL,                -- 生效时间，NULL 表示创建即生效
   expire_at       DATETIME        NULL,
   click_count     BIGINT UNSIGNED NOT NULL DEFAULT 0,
+  max_clicks      INT UNSIGNED    NULL,                -- 最大访问次数，NULL 表示不限
+  password_hash   VARCHAR(128)    NULL,                -- 访问密码 argon2 hash，NULL 表示无密码
+  targets         TEXT            NULL,                -- 按平台跳转规则 JSON {ios, android, desktop}
+  variants        TEXT            NULL,                -- A/B 分流目标 JSON [{url, weight}]
+  forward_query   BOOLEAN         NOT NULL DEFAULT FALSE, -- 是否透传访问时的查询参数
+  utm             TEXT            NULL,                -- UTM 参数模板 JSON {source, medium, campaign}
+  redirect_type   SMALLINT UNSIGNED NULL,              -- 跳转状态码 301/302/303/307/308，NULL 表示使用配置默认值
+  interstitial    BOOLEAN         NOT NULL DEFAULT FALSE, -- 每次访问先展示中间页
+  qr_public       BOOLEAN         NOT NULL DEFAULT FALSE, -- 是否允许通过 /s/{code}.qr 公开获取二维码
+  title           VARCHAR(200)    NULL,                -- 用户填写的标题
+  note            VARCHAR(1000)   NULL,                -- 用户备注
+  fallback_url    TEXT            NULL,                -- 过期后的跳转地址，NULL 时使用账号默认值
+  meta_title       VARCHAR(255)   NULL,                -- 抓取的目标页 <title>
+  meta_description VARCHAR(1000)  NULL,                -- 抓取的目标页描述
+  meta_favicon     TEXT           NULL,                -- 抓取的目标页图标地址
+  meta_fetched_at  DATETIME       NULL,                -- 元数据抓取时间
+  reminded_at     DATETIME        NULL,                -- 到期提醒发送时间，续期或修改有效时间后清空
+  status          TINYINT         NOT NULL DEFAULT 1,  -- 短链状态, 1=正常, 0=暂停
+  disabled_at     DATETIME        NULL,                -- 暂停时间
+  deleted_at      DATETIME        NULL,                -- 软删除时间，宽限期后由定时任务物理删除
   PRIMARY KEY (id),
   UNIQUE KEY uk_short (short_code),
   INDEX idx_user (user_id),                            -- 用户ID索引
+  UNIQUE KEY uk_user_dedupe (user_id, dedupe_hash),    -- 同用户同 URL 只有一条去重短链，并发创建时串行
   INDEX idx_created (created_at),
+  INDEX idx_user_created (user_id, created_at),        -- 列表游标翻页（按创建时间）
+  INDEX idx_user_clicks (user_id, click_count),        -- 列表游标翻页（按点击量）
+  INDEX idx_deleted (deleted_at),
+  INDEX idx_expire (expire_at),                        -- 到期提醒 / 过期删除
   CONSTRAINT fk_links_user FOREIGN KEY (user_id) REFERENCES users(id)
       ON DELETE CASCADE
       ON UPDATE CASCADE
@@ -23,6 +51,7 @@ CREATE TABLE visit_logs (
   ip VARCHAR(45) NOT NULL,
   user_agent TEXT,
   referer TEXT,
+  variant SMALLINT UNSIGNED NULL,                      -- A/B 分流命中的目标下标
   visit_time DATETIME NOT NULL
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
 
@@ -34,5 +63,31 @@ CREATE TABLE users (
     nickname     VARCHAR(32)  DEFAULT NULL COMMENT '昵称，可选',
     created_at   DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP COMMENT '注册时间',
     updated_at   DATETIME     NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP COMMENT '更新时间',
-    status       TINYINT      NOT NULL DEFAULT 1 COMMENT '账号状态, 1=正常, 0=禁用'
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
\ No newline at end of file
+    status       TINYINT      NOT NULL DEFAULT 1 COMMENT '账号状态, 1=正常, 0=禁用',
+    allow_permanent TINYINT(1) NOT NULL DEFAULT 0 COMMENT '是否允许创建永久短链',
+    fallback_url TEXT         NULL COMMENT '名下短链过期后的默认跳转地址'
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+CREATE TABLE tags (
+  id          BIGINT UNSIGNED NOT NULL AUTO_INCREMENT,
+  user_id     BIGINT UNSIGNED NOT NULL,
+  name        VARCHAR(32)     NOT NULL,                -- 标签名，同一用户下唯一（不区分大小写）
+  created_at  TIMESTAMP       NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (id),
+  UNIQUE KEY uk_user_name (user_id, name),
+  CONSTRAINT fk_tags_user FOREIGN KEY (user_id) REFERENCES users(id)
+      ON DELETE CASCADE
+      ON UPDATE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+
+CREATE TABLE link_tags (
+  link_id     BIGINT UNSIGNED NOT NULL,
+  tag_id      BIGINT UNSIGNED NOT NULL,
+  PRIMARY KEY (link_id, tag_id),
+  INDEX idx_tag (tag_id),                              -- 按标签筛选短链
+  CONSTRAINT fk_link_tags_link FOREIGN KEY (link_id) REFERENCES links(id)
+      ON DELETE CASCADE,
+  CONSTRAINT fk_link_tags_tag FOREIGN KEY (tag_id) REFERENCES tags(id)
+      ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
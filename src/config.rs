@@ -2,6 +2,7 @@ use serde::Deserialize;
 use config::{Config, Environment, ConfigError};
 use dotenvy;
 use std::env;
+use url::Url;
 
 #[derive(Debug, Deserialize)]
 pub struct AppConfig {
@@ -11,6 +12,8 @@ pub struct AppConfig {
     pub redis_url: String,
     /// 服务地址
     pub addr: String,
+    /// 对外访问短链的基础地址（含协议，如 https://sho.rt），用于返回的短链和二维码；未设置时使用 http://{addr}
+    pub public_base_url: Option<String>,
     /// JWT 密钥
     pub jwt_secret: String,
     /// 用户 token 的过期时间
@@ -19,6 +22,60 @@ pub struct AppConfig {
     pub shortlink_min_ttl: i64,
     /// 短链的最大过期时间
     pub shortlink_max_ttl: i64,
+    /// 创建短链时默认是否复用同一用户相同 URL 的未过期短链
+    #[serde(default)]
+    pub shortlink_dedupe_default: bool,
+    /// 短码生成策略：sequential（自增 id 编码）/ feistel（带密钥置换）/ random（随机）
+    #[serde(default = "default_short_code_strategy")]
+    pub short_code_strategy: String,
+    /// 短码字母表
+    #[serde(default = "default_short_code_alphabet")]
+    pub short_code_alphabet: String,
+    /// 短码最小长度（random 策略下为固定长度）
+    #[serde(default = "default_short_code_min_length")]
+    pub short_code_min_length: usize,
+    /// feistel 策略的密钥
+    #[serde(default)]
+    pub short_code_secret: String,
+    /// 自定义短码最小长度
+    #[serde(default = "default_custom_code_min_length")]
+    pub custom_code_min_length: usize,
+    /// 自定义短码最大长度（不超过 short_code 列长度 16）
+    #[serde(default = "default_custom_code_max_length")]
+    pub custom_code_max_length: usize,
+    /// 自定义短码保留字，逗号分隔，不区分大小写
+    #[serde(default)]
+    pub custom_code_reserved_words: String,
+    /// 自定义短码敏感词文件（可选），每行一个词
+    pub custom_code_profanity_file: Option<String>,
+    /// 目标地址允许的协议，逗号分隔
+    #[serde(default = "default_url_allowed_schemes")]
+    pub url_allowed_schemes: String,
+    /// 目标地址域名黑名单文件（可选），每行一个域名
+    pub url_blocklist_file: Option<String>,
+    /// 域名黑名单重新加载间隔（秒）
+    #[serde(default = "default_url_blocklist_reload_interval")]
+    pub url_blocklist_reload_interval: u64,
+    /// 本服务的对外域名（可选），逗号分隔，目标地址不能指向这些域名
+    pub shortlink_hosts: Option<String>,
+    /// 批量创建单次请求的最大条数
+    #[serde(default = "default_shortlink_batch_max_items")]
+    pub shortlink_batch_max_items: usize,
+    /// 批量创建每个事务写入的条数
+    #[serde(default = "default_shortlink_batch_chunk_size")]
+    pub shortlink_batch_chunk_size: usize,
+    /// CSV 导入单次最大行数
+    #[serde(default = "default_import_max_rows")]
+    pub import_max_rows: usize,
+    /// CSV 导入不超过该行数时同步返回报告，否则转为后台任务
+    #[serde(default = "default_import_sync_max_rows")]
+    pub import_sync_max_rows: usize,
+    /// 导入任务状态与报告的保留时间（秒）
+    #[serde(default = "default_import_job_ttl")]
+    pub import_job_ttl: i64,
+    /// 同时执行的后台导入任务数
+    #[serde(default = "default_import_max_concurrency")]
+    pub import_max_concurrency: usize,
     /// Redis 的最大过期时间
     pub redis_max_ttl: i64,
     /// Redis 的最小缓存时间
@@ -37,6 +94,12 @@ pub struct AppConfig {
     pub ip_user_login_fail_limit: i64,
     /// 单 IP + 账号失败锁定时长（秒）
     pub ip_user_login_fail_ttl: i64,
+    /// 单 IP + 短码访问密码连续错误次数阈值
+    #[serde(default = "default_link_password_fail_limit")]
+    pub link_password_fail_limit: i64,
+    /// 单 IP + 短码访问密码错误锁定时长（秒）
+    #[serde(default = "default_link_password_fail_ttl")]
+    pub link_password_fail_ttl: i64,
     /// 注册接口 - 每个IP每日注册次数上限
     pub ip_register_limit: i64,
     /// 注册接口 - 注册计数窗口（秒），86400=1天
@@ -76,17 +139,120 @@ pub struct AppConfig {
     pub bg_click_counts_sync_interval: u64,
     /// 访问日志同步任务的执行间隔（秒）
     pub bg_visit_logs_sync_interval: u64,
+    /// 回收站清理任务的执行间隔（秒）
+    #[serde(default = "default_bg_trash_purge_interval")]
+    pub bg_trash_purge_interval: u64,
+    /// 到期提醒任务的执行间隔（秒）
+    #[serde(default = "default_bg_expiry_reminder_interval")]
+    pub bg_expiry_reminder_interval: u64,
+    /// 提醒在多少小时内过期的短链
+    #[serde(default = "default_expiry_reminder_hours")]
+    pub expiry_reminder_hours: i64,
+    /// 到期提醒发送方式：log / file / webhook
+    #[serde(default = "default_expiry_notifier")]
+    pub expiry_notifier: String,
+    /// file 方式写入的文件，每行一条 JSON
+    pub expiry_notifier_file: Option<String>,
+    /// webhook 方式的回调地址
+    pub expiry_notifier_webhook_url: Option<String>,
+    /// webhook 请求超时时间（毫秒）
+    #[serde(default = "default_expiry_notifier_timeout_ms")]
+    pub expiry_notifier_timeout_ms: u64,
+    /// 回收站保留时长（秒），超过后物理删除
+    #[serde(default = "default_trash_retention_secs")]
+    pub trash_retention_secs: i64,
+    /// 默认跳转状态码：301/302/303/307/308
+    #[serde(default = "default_redirect_type_default")]
+    pub redirect_type_default: u16,
+    /// 永久跳转（301/308）允许浏览器缓存的时间（秒）
+    #[serde(default = "default_redirect_permanent_max_age")]
+    pub redirect_permanent_max_age: u64,
+    /// 创建或修改目标地址后是否在后台抓取目标页元数据
+    #[serde(default)]
+    pub link_metadata_enabled: bool,
+    /// 抓取目标页的超时时间（毫秒）
+    #[serde(default = "default_link_metadata_timeout_ms")]
+    pub link_metadata_timeout_ms: u64,
+    /// 抓取目标页最多读取的字节数
+    #[serde(default = "default_link_metadata_max_bytes")]
+    pub link_metadata_max_bytes: usize,
+    /// 元数据抓取作业队列容量，队列满时放弃抓取
+    #[serde(default = "default_link_metadata_queue_cap")]
+    pub link_metadata_queue_cap: usize,
+    /// 同时进行的元数据抓取数
+    #[serde(default = "default_link_metadata_max_concurrency")]
+    pub link_metadata_max_concurrency: usize,
+    /// 短码不存在时的错误页模板文件（可选）
+    pub error_page_not_found_file: Option<String>,
+    /// 短链过期时的错误页模板文件（可选）
+    pub error_page_expired_file: Option<String>,
+    /// 短链暂停时的错误页模板文件（可选）
+    pub error_page_paused_file: Option<String>,
+    /// 设置了过期跳转地址的过期短链保留天数，超过后由过期短链删除任务清理
+    #[serde(default = "default_expired_fallback_retention_days")]
+    pub expired_fallback_retention_days: i64,
+    /// 幂等键记录的保留时间（秒）
+    #[serde(default = "default_idempotency_ttl")]
+    pub idempotency_ttl: i64,
 }
 
+// 以下为后续新增配置项的缺省值，与新增前的行为保持一致，旧的 .env 无需修改即可启动
+fn default_short_code_strategy() -> String { "sequential".to_string() }
+fn default_short_code_alphabet() -> String { "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz".to_string() }
+fn default_short_code_min_length() -> usize { 1 }
+fn default_import_max_rows() -> usize { 10000 }
+fn default_import_sync_max_rows() -> usize { 100 }
+fn default_import_job_ttl() -> i64 { 86400 }
+fn default_import_max_concurrency() -> usize { 1 }
+fn default_link_metadata_timeout_ms() -> u64 { 3000 }
+fn default_link_metadata_max_bytes() -> usize { 262144 }
+fn default_link_metadata_queue_cap() -> usize { 100 }
+fn default_link_metadata_max_concurrency() -> usize { 2 }
+fn default_idempotency_ttl() -> i64 { 86400 }
+fn default_shortlink_batch_max_items() -> usize { 1000 }
+fn default_shortlink_batch_chunk_size() -> usize { 100 }
+fn default_custom_code_min_length() -> usize { 1 }
+fn default_custom_code_max_length() -> usize { 16 }
+fn default_bg_trash_purge_interval() -> u64 { 3600 }
+fn default_trash_retention_secs() -> i64 { 604800 }
+fn default_link_password_fail_limit() -> i64 { 5 }
+fn default_link_password_fail_ttl() -> i64 { 600 }
+fn default_redirect_type_default() -> u16 { 303 }
+fn default_redirect_permanent_max_age() -> u64 { 86400 }
+fn default_url_allowed_schemes() -> String { "http,https".to_string() }
+fn default_url_blocklist_reload_interval() -> u64 { 300 }
+fn default_bg_expiry_reminder_interval() -> u64 { 1800 }
+fn default_expiry_reminder_hours() -> i64 { 24 }
+fn default_expiry_notifier() -> String { "log".to_string() }
+fn default_expiry_notifier_timeout_ms() -> u64 { 3000 }
+fn default_expired_fallback_retention_days() -> i64 { 30 }
+
+
 impl AppConfig {
     pub fn from_env() -> Result<Self, ConfigError> {
         // 根据 ENV_FILE 环境变量指定的文件加载环境变量，默认使用 ".env"
         let env_file = env::var("ENV_FILE").unwrap_or_else(|_| ".env".to_string());
         dotenvy::from_filename(&env_file).ok();
-        Config::builder()
+        let cfg: Self = Config::builder()
             .add_source(Environment::default())
             .build()?
-            .try_deserialize()
+            .try_deserialize()?;
+
+        let base_url = cfg.base_url();
+        if !Url::parse(&base_url).is_ok_and(|u| matches!(u.scheme(), "http" | "https") && u.has_host()) {
+            return Err(ConfigError::Message(format!(
+                "PUBLIC_BASE_URL must be an http(s) URL, got {}", base_url
+            )));
+        }
+        Ok(cfg)
+    }
+
+    /// 对外短链的基础地址，不带末尾的 /
+    pub fn base_url(&self) -> String {
+        match self.public_base_url.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("http://{}", self.addr),
+        }
     }
 }
 
@@ -145,5 +311,13 @@ mod tests {
         assert_eq!(cfg.ip_register_limit, 5);
         assert_eq!(cfg.user_rate_limit, 200);
         assert_eq!(cfg.global_timeout_ms, 2000);
+        assert_eq!(cfg.base_url(), "http://127.0.0.1:3000");
+
+        // 对外基础地址必须带协议
+        unsafe { env::set_var("PUBLIC_BASE_URL", "https://sho.rt/"); }
+        assert_eq!(AppConfig::from_env().expect("load config").base_url(), "https://sho.rt");
+        unsafe { env::set_var("PUBLIC_BASE_URL", "sho.rt"); }
+        assert!(AppConfig::from_env().is_err());
+        unsafe { env::remove_var("PUBLIC_BASE_URL"); }
     }
 }
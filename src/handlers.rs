@@ -1,2 +1,3 @@
 pub mod shortlink;
-pub mod users;
\ No newline at end of file
+pub mod users;
+pub mod pages;
\ No newline at end of file
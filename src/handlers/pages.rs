@@ -0,0 +1,310 @@
+//! 面向访问者的 HTML 页面
+use std::fs;
+use axum::response::Html;
+use chrono::NaiveDateTime;
+
+use crate::config::AppConfig;
+use crate::models::link::LinkPreview;
+
+
+/// HTML 转义，用于把动态内容嵌入页面
+pub fn escape_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#x27;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+
+/// 访问密码表单，提交到当前地址
+pub fn password_form(error: Option<&str>) -> Html<String> {
+    let error = error
+        .map(|msg| format!(r#"<p class="error">{}</p>"#, escape_html(msg)))
+        .unwrap_or_default();
+
+    Html(format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Password required</title>
+<style>
+body {{ font-family: sans-serif; max-width: 360px; margin: 80px auto; padding: 0 16px; }}
+input, button {{ width: 100%; padding: 8px; margin-top: 8px; box-sizing: border-box; }}
+.error {{ color: #c00; }}
+</style>
+</head>
+<body>
+<h1>Password required</h1>
+<p>This link is protected. Enter the password to continue.</p>
+{error}
+<form method="post">
+<input type="password" name="password" autocomplete="off" autofocus required>
+<button type="submit">Continue</button>
+</form>
+</body>
+</html>
+"#))
+}
+
+
+/// 根据 Accept 判断访问者是否更偏好 HTML（浏览器），否则返回 JSON 错误
+/// 比较 text/html 与 application/json 的 q 值，通配符按最低优先级匹配；同等时选 JSON
+pub fn prefers_html(accept: Option<&str>) -> bool {
+    let Some(accept) = accept else {
+        return false;
+    };
+    let mut html_q: Option<(u8, f32)> = None;
+    let mut json_q: Option<(u8, f32)> = None;
+    for item in accept.split(',') {
+        let mut parts = item.split(';');
+        let media = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+        let q = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.trim().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        // (匹配精确度, q)：精确匹配优先于 type/* 和 */*
+        let matches = |target: &str| -> Option<u8> {
+            let (ty, _) = target.split_once('/')?;
+            if media == target {
+                Some(2)
+            } else if media == format!("{}/*", ty) {
+                Some(1)
+            } else if media == "*/*" {
+                Some(0)
+            } else {
+                None
+            }
+        };
+        for (target, best) in [("text/html", &mut html_q), ("application/json", &mut json_q)] {
+            if let Some(specificity) = matches(target) {
+                if best.is_none_or(|(s, _)| specificity > s) {
+                    *best = Some((specificity, q));
+                }
+            }
+        }
+    }
+    let html = html_q.map_or(0.0, |(_, q)| q);
+    let json = json_q.map_or(0.0, |(_, q)| q);
+    html > 0.0 && html > json
+}
+
+
+/// 短链不可用时展示的错误页类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorPageKind {
+    /// 短码不存在
+    NotFound,
+    /// 已过期
+    Expired,
+    /// 已暂停
+    Paused,
+}
+
+impl ErrorPageKind {
+    fn default_title(self) -> &'static str {
+        match self {
+            ErrorPageKind::NotFound => "Link not found",
+            ErrorPageKind::Expired => "Link expired",
+            ErrorPageKind::Paused => "Link paused",
+        }
+    }
+}
+
+
+/// 运维自定义的错误页模板，未配置时使用内置页面
+/// 模板中的 {{short_code}} 与 {{message}} 会被替换为转义后的内容
+pub struct ErrorPages {
+    not_found: Option<String>,
+    expired: Option<String>,
+    paused: Option<String>,
+}
+
+impl ErrorPages {
+    /// 启动时读取模板文件，文件不存在时报错
+    pub fn from_config(cfg: &AppConfig) -> Result<Self, String> {
+        let load = |path: &Option<String>| -> Result<Option<String>, String> {
+            path.as_deref()
+                .filter(|p| !p.is_empty())
+                .map(|p| fs::read_to_string(p).map_err(|e| format!("read error page {} error: {}", p, e)))
+                .transpose()
+        };
+        Ok(Self {
+            not_found: load(&cfg.error_page_not_found_file)?,
+            expired: load(&cfg.error_page_expired_file)?,
+            paused: load(&cfg.error_page_paused_file)?,
+        })
+    }
+
+    pub fn render(&self, kind: ErrorPageKind, short_code: &str, message: &str) -> Html<String> {
+        let template = match kind {
+            ErrorPageKind::NotFound => self.not_found.as_deref(),
+            ErrorPageKind::Expired => self.expired.as_deref(),
+            ErrorPageKind::Paused => self.paused.as_deref(),
+        };
+        let Some(template) = template else {
+            return error_page(kind.default_title(), message);
+        };
+        Html(template
+            .replace("{{short_code}}", &escape_html(short_code))
+            .replace("{{message}}", &escape_html(message)))
+    }
+}
+
+
+/// 内置错误页
+fn error_page(title: &str, message: &str) -> Html<String> {
+    Html(format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 360px; margin: 80px auto; padding: 0 16px; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{message}</p>
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        message = escape_html(message),
+    ))
+}
+
+
+/// 时间展示格式（UTC）
+fn format_time(time: NaiveDateTime) -> String {
+    format!("{} UTC", time.format("%Y-%m-%d %H:%M:%S"))
+}
+
+
+/// 短链预览 / 中间页
+/// destination 为 None 时不展示目标地址（如设置了访问密码），continue_url 为“继续访问”的链接
+pub fn link_preview(
+    short_code: &str,
+    preview: &LinkPreview,
+    destination: Option<&str>,
+    continue_url: &str,
+) -> Html<String> {
+    let destination = match destination {
+        Some(url) => format!(r#"<p class="url">{}</p>"#, escape_html(url)),
+        None => "<p>This link is password protected.</p>".to_string(),
+    };
+    let activate_at = preview.activate_at
+        .map(|t| format!("<dt>Active from</dt><dd>{}</dd>\n", format_time(t)))
+        .unwrap_or_default();
+    let expire_at = preview.expire_at
+        .map(format_time)
+        .unwrap_or_else(|| "Never".to_string());
+
+    Html(format!(r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="robots" content="noindex">
+<title>Link preview</title>
+<style>
+body {{ font-family: sans-serif; max-width: 480px; margin: 80px auto; padding: 0 16px; }}
+.url {{ word-break: break-all; padding: 8px; background: #f4f4f4; }}
+dt {{ font-weight: bold; margin-top: 8px; }}
+dd {{ margin: 0; }}
+a.button {{ display: block; text-align: center; padding: 8px; margin-top: 16px; border: 1px solid #888; color: inherit; text-decoration: none; }}
+</style>
+</head>
+<body>
+<h1>{code}</h1>
+<p>This short link leads to:</p>
+{destination}
+<dl>
+<dt>Created</dt><dd>{created_at}</dd>
+{activate_at}<dt>Expires</dt><dd>{expire_at}</dd>
+</dl>
+<a class="button" href="{continue_url}" rel="noopener noreferrer nofollow">Continue</a>
+</body>
+</html>
+"#,
+        code = escape_html(short_code),
+        created_at = format_time(preview.created_at.naive_utc()),
+        continue_url = escape_html(continue_url),
+    ))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html(r#"<a href="x">'&'</a>"#), "&lt;a href=&quot;x&quot;&gt;&#x27;&amp;&#x27;&lt;/a&gt;");
+        assert_eq!(escape_html("plain"), "plain");
+    }
+
+    #[test]
+    fn test_password_form_escapes_error() {
+        let Html(page) = password_form(Some("<script>"));
+        assert!(page.contains("&lt;script&gt;"));
+        assert!(!page.contains("<script>"));
+    }
+
+    #[test]
+    fn test_prefers_html() {
+        assert!(prefers_html(Some("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8")));
+        assert!(prefers_html(Some("text/*")));
+        assert!(!prefers_html(Some("application/json")));
+        assert!(!prefers_html(Some("*/*")));
+        assert!(!prefers_html(Some("text/html;q=0.5, application/json")));
+        assert!(!prefers_html(Some("text/html;q=0")));
+        assert!(!prefers_html(None));
+    }
+
+    #[test]
+    fn test_error_pages() {
+        let pages = ErrorPages {
+            not_found: None,
+            expired: Some("<p>{{short_code}} expired: {{message}}</p>".into()),
+            paused: None,
+        };
+        let Html(page) = pages.render(ErrorPageKind::Expired, "a<b", "Link expired");
+        assert_eq!(page, "<p>a&lt;b expired: Link expired</p>");
+
+        let Html(page) = pages.render(ErrorPageKind::NotFound, "abc", "Short code not found");
+        assert!(page.contains("<h1>Link not found</h1>"));
+        assert!(page.contains("Short code not found"));
+    }
+
+    #[test]
+    fn test_link_preview() {
+        let preview = LinkPreview {
+            long_url: "https://www.example.com/?a=1&b=<2>".into(),
+            created_at: Utc::now(),
+            activate_at: None,
+            expire_at: None,
+            status: 1,
+            password_protected: false,
+        };
+        let Html(page) = link_preview("abc", &preview, Some(&preview.long_url), "/s/abc");
+        assert!(page.contains("https://www.example.com/?a=1&amp;b=&lt;2&gt;"));
+        assert!(page.contains(r#"href="/s/abc""#));
+        assert!(page.contains("Never"));
+
+        let Html(page) = link_preview("abc", &preview, None, "/s/abc");
+        assert!(!page.contains("www.example.com"));
+    }
+}
@@ -1,39 +1,441 @@
 use axum::{
-    extract::{ConnectInfo, Path, Query, State}, 
-    http::StatusCode, 
-    response::Redirect, 
+    body::Body,
+    extract::{ConnectInfo, Path, Query, RawQuery, State}, 
+    http::{header, HeaderMap, StatusCode, Uri}, 
+    response::{IntoResponse, Response}, 
     Extension, 
+    Form,
     Json
 };
 use axum_extra::TypedHeader;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
 use chrono_tz::Tz;
-use headers::{UserAgent, Referer};
+use headers::UserAgent;
 use std::{sync::Arc, net::SocketAddr};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use validator::{Validate, ValidationError};
 use tracing::warn;
 
 use crate::{
     state::AppState, 
-    services::ShortlinkService, 
-    models::{user::User, link::LinkView}
+    handlers::pages,
+    services::{qr, LinkResolution, LinkUnavailable, ShortlinkService, Visitor}, 
+    models::{
+        user::User,
+        link::{LinkView, LINK_STATUS_ACTIVE, LINK_STATUS_PAUSED},
+    }
 };
 
 
+/// 幂等键请求头
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// 幂等键最大长度
+const IDEMPOTENCY_KEY_MAX_LEN: usize = 128;
+
+
+/// 区分字段缺省与显式 null：缺省为 None，null 为 Some(None)
+fn deserialize_some<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
+
+
+/// 按平台跳转规则，未命中的平台跳转到默认长链
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct LinkTargets {
+    /// iOS：App Store 等
+    #[validate(url(message = "Invalid iOS URL"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ios: Option<String>,
+    /// Android：Google Play / intent 链接
+    #[validate(url(message = "Invalid Android URL"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub android: Option<String>,
+    /// 桌面端
+    #[validate(url(message = "Invalid desktop URL"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desktop: Option<String>,
+}
+
+/// A/B 分流目标，按权重随机选择
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct LinkVariant {
+    #[validate(url(message = "Invalid variant URL"))]
+    pub url: String,
+    #[validate(range(min = 1, max = 10000, message = "Weight must be between 1 and 10000"))]
+    pub weight: u32,
+}
+
+/// UTM 参数模板，跳转时追加到目标地址
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Validate)]
+pub struct UtmTemplate {
+    #[validate(length(min = 1, max = 100, message = "utm source must be between 1 and 100 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "utm medium must be between 1 and 100 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub medium: Option<String>,
+    #[validate(length(min = 1, max = 100, message = "utm campaign must be between 1 and 100 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub campaign: Option<String>,
+}
+
+impl UtmTemplate {
+    /// 已设置的 utm_* 参数
+    pub fn pairs(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("utm_source", self.source.as_deref()),
+            ("utm_medium", self.medium.as_deref()),
+            ("utm_campaign", self.campaign.as_deref()),
+        ]
+        .into_iter()
+        .filter_map(|(k, v)| v.map(|v| (k, v)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs().next().is_none()
+    }
+}
+
+/// 跳转状态码
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub enum RedirectType {
+    /// 301 永久跳转
+    MovedPermanently,
+    /// 302 临时跳转
+    Found,
+    /// 303 临时跳转（改用 GET）
+    SeeOther,
+    /// 307 临时跳转（保留请求方法）
+    TemporaryRedirect,
+    /// 308 永久跳转（保留请求方法）
+    PermanentRedirect,
+}
+
+impl RedirectType {
+    pub fn status(self) -> StatusCode {
+        match self {
+            Self::MovedPermanently => StatusCode::MOVED_PERMANENTLY,
+            Self::Found => StatusCode::FOUND,
+            Self::SeeOther => StatusCode::SEE_OTHER,
+            Self::TemporaryRedirect => StatusCode::TEMPORARY_REDIRECT,
+            Self::PermanentRedirect => StatusCode::PERMANENT_REDIRECT,
+        }
+    }
+
+    /// 永久跳转会被浏览器缓存，再次访问不经过服务端
+    pub fn is_permanent(self) -> bool {
+        matches!(self, Self::MovedPermanently | Self::PermanentRedirect)
+    }
+}
+
+impl TryFrom<u16> for RedirectType {
+    type Error = String;
+
+    fn try_from(code: u16) -> Result<Self, Self::Error> {
+        match code {
+            301 => Ok(Self::MovedPermanently),
+            302 => Ok(Self::Found),
+            303 => Ok(Self::SeeOther),
+            307 => Ok(Self::TemporaryRedirect),
+            308 => Ok(Self::PermanentRedirect),
+            _ => Err(format!("Invalid redirect type: {}, expected 301/302/303/307/308", code)),
+        }
+    }
+}
+
+impl From<RedirectType> for u16 {
+    fn from(redirect_type: RedirectType) -> Self {
+        redirect_type.status().as_u16()
+    }
+}
+
 /// 客户端请求：创建短链
-#[derive(Deserialize, Validate)]
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
 pub struct ShortlinkCreateReq {
     #[validate(url(message = "Invalid URL"))]
     pub url: String,
-    pub ttl: Option<i64>,
+    /// 缺省时使用最小有效时间；传 null 表示永久，需要账号具备永久短链权限
+    #[serde(default, deserialize_with = "deserialize_some", skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<Option<i64>>,
+    /// 创建永久短链，需要账号具备权限
+    pub permanent: Option<bool>,
     pub short_code: Option<String>,
+    /// 是否复用相同 URL 的未过期短链，未传时使用配置默认值；显式指定 ttl 或 permanent 时不复用
+    pub dedupe: Option<bool>,
+    /// 最大访问次数，达到后短链失效；缺省不限
+    #[validate(range(min = 1, message = "max_clicks must be at least 1"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_clicks: Option<u32>,
+    /// 生效时间（RFC 3339），生效前访问返回 403；有效时间从生效时间起算
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activate_at: Option<DateTime<Utc>>,
+    /// 访问密码，设置后访问需先输入密码
+    #[validate(length(min = 4, max = 128, message = "Password must be between 4 and 128 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// 按平台跳转规则
+    #[validate(nested)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<LinkTargets>,
+    /// A/B 分流目标（2~10 个），未命中平台规则的访问按权重分流，url 不参与分流
+    #[validate(length(min = 2, max = 10, message = "Variants must be between 2 and 10"), nested)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Vec<LinkVariant>>,
+    /// 是否把访问时携带的查询参数透传到目标地址，缺省不透传
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_query: Option<bool>,
+    /// UTM 参数模板
+    #[validate(nested)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utm: Option<UtmTemplate>,
+    /// 跳转状态码，缺省使用配置默认值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_type: Option<RedirectType>,
+    /// 每次访问先展示中间页，由访问者确认后跳转，适用于指向外部或不可信域名的短链
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interstitial: Option<bool>,
+    /// 是否允许通过 /s/{code}.qr 公开获取二维码，缺省不允许
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qr_public: Option<bool>,
+    /// 标签（最多 20 个），用于分组和按活动统计
+    #[validate(custom(function = "validate_tags"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// 标题，便于在列表中识别
+    #[validate(length(min = 1, max = 200, message = "Title must be between 1 and 200 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// 备注
+    #[validate(length(min = 1, max = 1000, message = "Note must be between 1 and 1000 characters"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// 过期后的跳转地址，缺省使用账号默认值
+    #[validate(url(message = "Invalid fallback URL"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_url: Option<String>,
+}
+
+/// 访问者提交：短链访问密码
+#[derive(Deserialize)]
+pub struct LinkPasswordForm {
+    pub password: String,
 }
 
 /// 服务端返回：短链创建结果
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ShortlinkCreateResp {
     pub short_url: String,
+    /// 提示信息，如永久跳转被浏览器缓存后点击量无法统计
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+/// 客户端请求：编辑短链，仅更新传入的字段
+#[derive(Deserialize, Validate)]
+pub struct LinkUpdateReq {
+    #[validate(url(message = "Invalid URL"))]
+    pub url: Option<String>,
+    /// 新有效时间，从当前时间起算；尚未生效的短链从生效时间起算
+    pub ttl: Option<i64>,
+    pub short_code: Option<String>,
+    /// 替换按平台跳转规则，传空对象表示清除
+    #[validate(nested)]
+    pub targets: Option<LinkTargets>,
+    /// 跳转状态码，传 null 表示恢复配置默认值
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub redirect_type: Option<Option<RedirectType>>,
+    pub interstitial: Option<bool>,
+    pub qr_public: Option<bool>,
+    /// 替换标签，传空数组表示清除全部标签
+    #[validate(custom(function = "validate_tags"))]
+    pub tags: Option<Vec<String>>,
+    /// 标题，传 null 表示清除
+    #[validate(length(min = 1, max = 200, message = "Title must be between 1 and 200 characters"))]
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub title: Option<Option<String>>,
+    /// 备注，传 null 表示清除
+    #[validate(length(min = 1, max = 1000, message = "Note must be between 1 and 1000 characters"))]
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub note: Option<Option<String>>,
+    /// 过期后的跳转地址，传 null 表示恢复账号默认值
+    #[validate(url(message = "Invalid fallback URL"))]
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub fallback_url: Option<Option<String>>,
+}
+
+/// 单条短链最多标签数
+pub const MAX_TAGS_PER_LINK: usize = 20;
+
+/// 校验标签：数量不超过上限，去掉首尾空白后长度在 1~32 之间
+fn validate_tags(tags: &[String]) -> Result<(), ValidationError> {
+    if tags.len() > MAX_TAGS_PER_LINK {
+        return Err(ValidationError::new("Too_many_tags"));
+    }
+    for tag in tags {
+        let len = tag.trim().chars().count();
+        if !(1..=32).contains(&len) {
+            return Err(ValidationError::new("Invalid_tag"));
+        }
+    }
+    Ok(())
+}
+
+/// 客户端请求：续期短链
+#[derive(Deserialize)]
+pub struct LinkRenewReq {
+    /// 延长的秒数，缺省为最小有效时间
+    pub ttl: Option<i64>,
+}
+
+/// 服务端返回：续期后的过期时间
+#[derive(Serialize, Deserialize)]
+pub struct LinkRenewResp {
+    pub expire_at: DateTime<Utc>,
+}
+
+/// 批量创建单条失败原因
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchErrorCode {
+    /// URL 不合法
+    InvalidUrl,
+    /// 有效时间越界
+    TtlOutOfRange,
+    /// 账号无权创建永久短链
+    PermanentNotAllowed,
+    /// 自定义短码不符合规则
+    InvalidCode,
+    /// 自定义短码已被占用
+    CodeTaken,
+    /// 最大访问次数不合法
+    InvalidMaxClicks,
+    /// 访问密码不合法
+    InvalidPassword,
+    /// A/B 分流目标不合法
+    InvalidVariants,
+    /// 标签不合法
+    InvalidTags,
+    /// 标题不合法
+    InvalidTitle,
+    /// 导入的 CSV 行无法解析
+    InvalidRow,
+    /// 目标地址被安全策略拒绝
+    UrlNotAllowed,
+    /// 服务端错误
+    Internal,
+}
+
+/// 批量创建单条失败详情
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchItemError {
+    pub code: BatchErrorCode,
+    pub message: String,
+}
+
+/// 批量创建单条结果，short_url 与 error 二选一
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub short_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<BatchItemError>,
+}
+
+impl BatchItemResult {
+    pub fn succeeded(index: usize, short_url: String) -> Self {
+        Self { index, short_url: Some(short_url), error: None }
+    }
+
+    pub fn failed(index: usize, code: BatchErrorCode, message: String) -> Self {
+        Self { index, short_url: None, error: Some(BatchItemError { code, message }) }
+    }
+}
+
+/// 服务端返回：批量创建结果（按请求下标）
+#[derive(Serialize, Deserialize)]
+pub struct BatchCreateResp {
+    pub results: Vec<BatchItemResult>,
+}
+
+/// 导入任务状态
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStatus {
+    /// 已排队
+    Pending,
+    /// 导入中
+    Running,
+    /// 已完成，逐行结果见 results
+    Done,
+    /// 任务异常中止
+    Failed,
+}
+
+/// 服务端返回：导入报告
+/// results 按 CSV 数据行下标（不含表头，从 0 开始），后台任务完成前为空
+#[derive(Serialize, Deserialize)]
+pub struct ImportReport {
+    /// 后台任务编号，同步导入时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<String>,
+    pub status: ImportStatus,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BatchItemResult>,
+    /// 任务异常中止的原因
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ImportReport {
+    pub fn pending(job_id: String, total: usize) -> Self {
+        Self {
+            job_id: Some(job_id),
+            status: ImportStatus::Pending,
+            total,
+            succeeded: 0,
+            failed: 0,
+            results: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn finished(job_id: Option<String>, results: Vec<BatchItemResult>) -> Self {
+        let failed = results.iter().filter(|r| r.error.is_some()).count();
+        Self {
+            job_id,
+            status: ImportStatus::Done,
+            total: results.len(),
+            succeeded: results.len() - failed,
+            failed,
+            results,
+            error: None,
+        }
+    }
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// 每行一条 JSON
+    Ndjson,
+}
+
+/// 导出参数，筛选条件与列表接口相同
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
 }
 
 /// 默认时区
@@ -50,6 +452,27 @@ fn validate_tz(timezone: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// 列表排序字段
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSort {
+    #[default]
+    CreatedAt,
+    ClickCount,
+    /// 永久短链视为最晚过期
+    ExpireAt,
+    ShortCode,
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
 /// 查询参数
 #[derive(Debug, Default, Deserialize, Validate)]
 pub struct LinkQuery {
@@ -57,6 +480,7 @@ pub struct LinkQuery {
     pub user_id: Option<u64>, // 用户ID
     pub short_code: Option<String>, // 短码
     pub long_url: Option<String>, // 长 URL
+    pub tag: Option<String>, // 标签
     pub click_count: Option<u64>, // 点击量
     pub date_from:    Option<NaiveDateTime>, // 日期范围
     pub date_to:      Option<NaiveDateTime>,
@@ -66,12 +490,21 @@ pub struct LinkQuery {
     #[serde(default = "default_timezone")]
     #[validate(custom(function = "validate_tz"))]
     pub timezone: String,
+    // ---排序---
+    #[serde(default)]
+    pub sort: LinkSort,
+    #[serde(default)]
+    pub order: SortOrder,
     // ---分页---
     #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
     #[serde(default = "default_limit")]
     pub limit: u64,
     #[serde(default)]
     pub offset: u64,
+    /// 上一页返回的 next_cursor，传入后从游标位置继续并忽略 offset；排序方式需与上一页一致
+    pub cursor: Option<String>,
+    /// 是否统计总数，缺省仅在首页（未传 cursor）统计
+    pub with_count: Option<bool>,
 }
 
 /// 默认每页数量
@@ -82,7 +515,12 @@ fn default_limit() -> u64 { 10 }
 #[derive(Serialize, Deserialize)]
 pub struct LinkList {
     pub links: Vec<LinkView>,
-    pub count: i64,
+    /// 满足条件的总数，未统计时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub count: Option<i64>,
+    /// 下一页游标，没有更多数据时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 
@@ -94,6 +532,28 @@ pub struct DeleteLinksReq {
 }
 
 
+/// 恢复短链请求
+#[derive(Deserialize, Validate)]
+pub struct RestoreLinksReq {
+    #[validate(length(min = 1, max = 50, message = "Ids must be between 1 and 50"))]
+    pub ids: Vec<u64>,
+}
+
+
+/// 回收站查询参数
+#[derive(Debug, Deserialize, Validate)]
+pub struct TrashQuery {
+    #[serde(default = "default_timezone")]
+    #[validate(custom(function = "validate_tz"))]
+    pub timezone: String,
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    #[serde(default = "default_limit")]
+    pub limit: u64,
+    #[serde(default)]
+    pub offset: u64,
+}
+
+
 /// 点击量统计（按天）
 #[derive(Debug, Deserialize, Validate)]
 pub struct LinkStatsQuery {
@@ -106,14 +566,60 @@ pub struct LinkStatsQuery {
     pub timezone: String, // 选填：时区偏移
 }
 
+/// 服务端返回：A/B 分流目标点击量
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VariantStats {
+    pub variant: u16,
+    pub url: String,
+    pub weight: u32,
+    pub clicks: i64,
+}
+
+/// 服务端返回：按标签汇总的短链数和点击量
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagStats {
+    pub tag: String,
+    pub links: i64,
+    pub clicks: i64,
+}
+
 /// 默认天数
 fn default_days() -> u8 { 30 }
 
+/// 二维码图片格式
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QrFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+/// 二维码参数
+#[derive(Debug, Deserialize, Validate)]
+pub struct QrQuery {
+    #[serde(default)]
+    pub format: QrFormat,
+    /// 图片边长（像素）
+    #[serde(default = "default_qr_size")]
+    #[validate(range(min = 64, max = 2048, message = "Size must be between 64 and 2048"))]
+    pub size: u32,
+    /// 四周留白（模块数）
+    #[serde(default = "default_qr_margin")]
+    #[validate(range(max = 16, message = "Margin must be at most 16"))]
+    pub margin: u32,
+}
+
+fn default_qr_size() -> u32 { 256 }
+
+fn default_qr_margin() -> u32 { 4 }
+
 
 /// 创建短链
 pub async fn create(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<User>,
+    headers: HeaderMap,
     Json(payload): Json<ShortlinkCreateReq>,
 ) -> Result<Json<ShortlinkCreateResp>, (StatusCode, String)> {
     // 校验 url
@@ -122,57 +628,281 @@ pub async fn create(
         return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
     }
 
-    // 校验短链有效时间
-    let config = state.config.read().await;
-    let min_ttl = config.shortlink_min_ttl;
-    let max_ttl = config.shortlink_max_ttl;
-
-    let ttl = match payload.ttl {
-        Some(ttl) => {
-            if ttl < min_ttl || ttl > max_ttl {
-                warn!("create_shortlink: TTL越界: user_id={}, ttl={}, min={}, max={}", user.id, ttl, min_ttl, max_ttl);
-                return Err((
-                    StatusCode::BAD_REQUEST, 
-                    format!("TTL must be between {} and {}", min_ttl, max_ttl)
-                ));
-            }
-            ttl
+    // 校验幂等键
+    let idempotency_key = match headers.get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => {
+            let key = value
+                .to_str()
+                .ok()
+                .map(str::trim)
+                .filter(|k| !k.is_empty() && k.len() <= IDEMPOTENCY_KEY_MAX_LEN)
+                .ok_or_else(|| {
+                    warn!("create_shortlink: Idempotency-Key 非法: user_id={}", user.id);
+                    (
+                        StatusCode::BAD_REQUEST,
+                        format!("Idempotency-Key must be 1 to {} visible characters", IDEMPOTENCY_KEY_MAX_LEN),
+                    )
+                })?;
+            Some(key.to_string())
         },
-        None => min_ttl,
+        None => None,
+    };
+
+    // 校验短链有效时间
+    let (min_ttl, max_ttl, dedupe_default) = {
+        let config = state.config.read().await;
+        (config.shortlink_min_ttl, config.shortlink_max_ttl, config.shortlink_dedupe_default)
     };
 
+    // ttl 为 None 表示永久短链
+    let ttl = ShortlinkService::resolve_ttl(
+        payload.ttl,
+        payload.permanent.unwrap_or(false),
+        user.allow_permanent,
+        min_ttl,
+        max_ttl,
+    ).inspect_err(|(_, msg)| {
+        warn!("create_shortlink: 有效时间不合法: user_id={}, ttl={:?}, permanent={:?}, error={}", user.id, payload.ttl, payload.permanent, msg);
+    })?;
+
+    let dedupe = ShortlinkService::dedupe_enabled(&payload, dedupe_default);
+
+    // 携带幂等键时由服务层负责去重与回放
+    if let Some(key) = idempotency_key {
+        let resp = ShortlinkService::create_shortlink_idempotent(
+            &state,
+            &payload,
+            ttl,
+            dedupe,
+            user.id,
+            &key,
+        ).await?;
+        return Ok(Json(resp));
+    }
+
     // 创建短链
-    let short_url = ShortlinkService::create_shortlink(
+    let resp = ShortlinkService::create_shortlink(
         &state, 
-        &payload.url,
-        payload.short_code,
+        &payload,
         ttl,
+        dedupe,
         user.id
     ).await?;
     
-    Ok(Json(ShortlinkCreateResp { short_url }))
+    Ok(Json(resp))
+}
+
+/// 批量创建短链
+pub async fn create_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Json(items): Json<Vec<ShortlinkCreateReq>>,
+) -> Result<Json<BatchCreateResp>, (StatusCode, String)> {
+    let max_items = state.config.read().await.shortlink_batch_max_items;
+    if items.is_empty() || items.len() > max_items {
+        warn!("create_batch: 批量条数越界: user_id={}, len={}, max={}", user.id, items.len(), max_items);
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Batch size must be between 1 and {}", max_items),
+        ));
+    }
+
+    let results = ShortlinkService::create_shortlinks_batch(
+        &state,
+        &items,
+        &user,
+    ).await?;
+
+    Ok(Json(BatchCreateResp { results }))
+}
+
+/// 构造跳转响应
+/// 永久跳转只允许访问者自己的浏览器缓存 max_age 秒，不允许共享缓存；
+/// 临时跳转或 max_age 为 0 时禁止缓存以保证每次访问都计入统计
+fn redirect_response(url: &str, redirect_type: RedirectType, max_age: u64) -> Response {
+    let cache_control = if redirect_type.is_permanent() && max_age > 0 {
+        format!("private, max-age={}", max_age)
+    } else {
+        "private, no-store".to_string()
+    };
+    (
+        redirect_type.status(),
+        [
+            (header::LOCATION, url.to_string()),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+    ).into_response()
+}
+
+/// 服务端返回：面向 API 客户端的错误
+#[derive(Serialize, Deserialize)]
+pub struct ErrorResp {
+    pub error: String,
+}
+
+/// 跳转失败时的响应
+/// 已过期且设置了过期跳转地址时临时跳转过去；不存在 / 已过期 / 已暂停按 Accept 返回 HTML 错误页或 JSON
+async fn unavailable_response(
+    state: &AppState,
+    short_code: &str,
+    headers: &HeaderMap,
+    (status, message): (StatusCode, String),
+) -> Response {
+    if !matches!(status, StatusCode::NOT_FOUND | StatusCode::GONE) {
+        return (status, message).into_response();
+    }
+    let kind = match ShortlinkService::resolve_unavailable(state, short_code).await {
+        Ok(Some(LinkUnavailable::Fallback(url))) => {
+            return redirect_response(&url, RedirectType::Found, 0);
+        },
+        Ok(Some(LinkUnavailable::Page(kind))) => kind,
+        Ok(None) => return (status, message).into_response(),
+        Err(e) => return e.into_response(),
+    };
+
+    let accept = headers.get(header::ACCEPT).and_then(|v| v.to_str().ok());
+    if pages::prefers_html(accept) {
+        (status, state.error_pages.render(kind, short_code, &message)).into_response()
+    } else {
+        (status, Json(ErrorResp { error: message })).into_response()
+    }
 }
 
 /// 重定向
 pub async fn redirect(
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     TypedHeader(user_agent): TypedHeader<UserAgent>,
-    referer: Option<TypedHeader<Referer>>,
     Path(short_code): Path<String>,
+    uri: Uri,
+    headers: HeaderMap,
     State(state): State<Arc<AppState>>,
-) -> Result<Redirect, (StatusCode, String)> {
-    let ip = addr.ip().to_string();
-    let ua = user_agent.as_str();
-    let ref_ = referer.map(|r| r.to_string()).unwrap_or_default();
-    let long_url = ShortlinkService::get_long_url(
-        &ip, 
-        ua, 
-        &ref_, 
-        &state, 
-        &short_code
-    ).await?;
-    
-    Ok(Redirect::to(&long_url))
+) -> Result<Response, (StatusCode, String)> {
+    // 短码后加 + 时展示预览页
+    if let Some(code) = short_code.strip_suffix('+') {
+        return preview_page(&state, code).await;
+    }
+    // 短码后加 .qr 时返回公开二维码
+    if let Some(code) = short_code.strip_suffix(".qr") {
+        let Query(q) = Query::<QrQuery>::try_from_uri(&uri).map_err(|e| {
+            warn!("redirect: 二维码参数解析失败: short_code={}, error={}", code, e);
+            (StatusCode::BAD_REQUEST, format!("Invalid query: {}", e))
+        })?;
+        let short_url = ShortlinkService::public_qr_url(&state, code).await?;
+        return qr_response(&short_url, &q);
+    }
+
+    let visitor = visitor(addr, &user_agent, &headers);
+    Ok(visit(&state, &visitor, &short_code, uri.query(), &headers, None, false).await)
+}
+
+/// 中间页“继续访问”：访问者确认后才计点击并跳转
+pub async fn redirect_continue(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    Path(short_code): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+) -> Response {
+    let visitor = visitor(addr, &user_agent, &headers);
+    visit(&state, &visitor, &short_code, query.as_deref(), &headers, None, true).await
+}
+
+/// 预览页：展示目标地址、创建时间和过期时间，不计点击
+pub async fn preview(
+    Path(short_code): Path<String>,
+    State(state): State<Arc<AppState>>,
+) -> Result<Response, (StatusCode, String)> {
+    preview_page(&state, &short_code).await
+}
+
+async fn preview_page(state: &AppState, short_code: &str) -> Result<Response, (StatusCode, String)> {
+    let preview = ShortlinkService::preview_link(state, short_code).await?;
+    // 设置了访问密码的短链不展示目标地址
+    let destination = (!preview.password_protected).then_some(preview.long_url.as_str());
+    let continue_url = format!("/s/{}", short_code);
+
+    Ok(pages::link_preview(short_code, &preview, destination, &continue_url).into_response())
+}
+
+/// 提交访问密码后跳转
+/// 密码错误或被限流时重新展示表单
+pub async fn redirect_with_password(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    Path(short_code): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<LinkPasswordForm>,
+) -> Response {
+    // 表单未指定 action，提交地址保留了原始查询参数
+    let visitor = visitor(addr, &user_agent, &headers);
+    visit(&state, &visitor, &short_code, query.as_deref(), &headers, Some(&form.password), false).await
+}
+
+/// 中间页确认后提交访问密码
+pub async fn redirect_continue_with_password(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    Path(short_code): Path<String>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    State(state): State<Arc<AppState>>,
+    Form(form): Form<LinkPasswordForm>,
+) -> Response {
+    let visitor = visitor(addr, &user_agent, &headers);
+    visit(&state, &visitor, &short_code, query.as_deref(), &headers, Some(&form.password), true).await
+}
+
+fn visitor(addr: SocketAddr, user_agent: &UserAgent, headers: &HeaderMap) -> Visitor {
+    Visitor {
+        ip: addr.ip().to_string(),
+        user_agent: user_agent.as_str().to_string(),
+        referer: headers
+            .get(header::REFERER)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// 访问短链并生成响应
+/// confirmed 为访问者已在中间页确认继续；中间页的“继续访问”指向 /s/{code}/continue 并保留原始查询参数
+async fn visit(
+    state: &AppState,
+    visitor: &Visitor,
+    short_code: &str,
+    query: Option<&str>,
+    headers: &HeaderMap,
+    password: Option<&str>,
+    confirmed: bool,
+) -> Response {
+    let resolution = ShortlinkService::get_long_url(
+        visitor,
+        state,
+        short_code,
+        query,
+        password,
+        confirmed,
+    ).await;
+
+    match resolution {
+        Ok(LinkResolution::Redirect { url, redirect_type, max_age }) => redirect_response(&url, redirect_type, max_age),
+        // 设置了访问密码：返回密码表单
+        Ok(LinkResolution::PasswordRequired) => pages::password_form(None).into_response(),
+        Ok(LinkResolution::Interstitial { destination, preview }) => {
+            let continue_url = match query {
+                Some(q) => format!("/s/{}/continue?{}", short_code, q),
+                None => format!("/s/{}/continue", short_code),
+            };
+            pages::link_preview(short_code, &preview, destination.as_deref(), &continue_url).into_response()
+        },
+        Err((status @ (StatusCode::UNAUTHORIZED | StatusCode::TOO_MANY_REQUESTS), msg)) => {
+            (status, pages::password_form(Some(&msg))).into_response()
+        },
+        Err(e) => unavailable_response(state, short_code, headers, e).await,
+    }
 }
 
 /// 获取短链列表
@@ -189,14 +919,71 @@ pub async fn list_links(
 
     q.user_id = Some(user.id);
     
-    let (links, count) = ShortlinkService::list_links(
+    let list = ShortlinkService::list_links(
         &state,
         &q,
         q.limit,
         q.offset,
     ).await?;
 
-    Ok(Json(LinkList { links, count }))
+    Ok(Json(list))
+}
+
+/// 导出短链：按列表接口的筛选条件流式返回全部匹配的短链，不分页
+pub async fn export_links(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Query(export): Query<ExportQuery>,
+    Query(mut q): Query<LinkQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    if let Err(e) = q.validate() {
+        warn!("export_links: 查询参数校验失败: user_id={}, error={}", user.id, e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    q.user_id = Some(user.id);
+
+    let (content_type, filename) = match export.format {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "links.csv"),
+        ExportFormat::Ndjson => ("application/x-ndjson", "links.ndjson"),
+    };
+    let stream = ShortlinkService::export_links(state, q, export.format).await;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(stream),
+    ).into_response())
+}
+
+/// 从 CSV 导入短链
+/// 表头需包含 long_url，可选 short_code、ttl、title；行数较多时转为后台任务并返回 202
+pub async fn import_links(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    body: String,
+) -> Result<Response, (StatusCode, String)> {
+    let report = ShortlinkService::import_links(&state, &body, &user).await?;
+
+    let status = if report.status == ImportStatus::Pending {
+        StatusCode::ACCEPTED
+    } else {
+        StatusCode::OK
+    };
+    Ok((status, Json(report)).into_response())
+}
+
+/// 查询导入任务进度和结果
+pub async fn import_status(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(job_id): Path<String>,
+) -> Result<Json<ImportReport>, (StatusCode, String)> {
+    let report = ShortlinkService::import_status(&state, user.id, &job_id).await?;
+
+    Ok(Json(report))
 }
 
 /// 删除短链
@@ -219,6 +1006,131 @@ pub async fn delete_links(
     Ok(())
 }
 
+/// 回收站列表
+pub async fn list_trash(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Query(q): Query<TrashQuery>,
+) -> Result<Json<LinkList>, (StatusCode, String)> {
+    if let Err(e) = q.validate() {
+        warn!("list_trash: 查询参数校验失败: user_id={}, error={}", user.id, e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let (links, count) = ShortlinkService::list_trash(
+        &state,
+        user.id,
+        &q.timezone,
+        q.limit,
+        q.offset,
+    ).await?;
+
+    Ok(Json(LinkList { links, count: Some(count), next_cursor: None }))
+}
+
+/// 从回收站恢复短链
+pub async fn restore_links(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Json(payload): Json<RestoreLinksReq>,
+) -> Result<(), (StatusCode, String)> {
+    if let Err(e) = payload.validate() {
+        warn!("restore_links: 恢复参数校验失败: user_id={}, error={}", user.id, e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    ShortlinkService::restore_links(
+        &state,
+        payload.ids,
+        user.id,
+    ).await?;
+
+    Ok(())
+}
+
+/// 编辑短链
+pub async fn update_link(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(link_id): Path<u64>,
+    Json(payload): Json<LinkUpdateReq>,
+) -> Result<(), (StatusCode, String)> {
+    if let Err(e) = payload.validate() {
+        warn!("update_link: 参数校验失败: user_id={}, link_id={}, error={}", user.id, link_id, e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    if payload.url.is_none()
+        && payload.ttl.is_none()
+        && payload.short_code.is_none()
+        && payload.targets.is_none()
+        && payload.redirect_type.is_none()
+        && payload.interstitial.is_none()
+        && payload.qr_public.is_none()
+        && payload.tags.is_none()
+        && payload.title.is_none()
+        && payload.note.is_none()
+        && payload.fallback_url.is_none()
+    {
+        warn!("update_link: 无可更新字段: user_id={}, link_id={}", user.id, link_id);
+        return Err((StatusCode::BAD_REQUEST, "Nothing to update".into()));
+    }
+
+    ShortlinkService::update_link(
+        &state,
+        link_id,
+        user.id,
+        &payload,
+    ).await?;
+
+    Ok(())
+}
+
+/// 暂停短链
+pub async fn pause_link(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(link_id): Path<u64>,
+) -> Result<(), (StatusCode, String)> {
+    ShortlinkService::set_link_status(
+        &state,
+        link_id,
+        user.id,
+        LINK_STATUS_PAUSED,
+    ).await
+}
+
+/// 恢复短链
+pub async fn resume_link(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(link_id): Path<u64>,
+) -> Result<(), (StatusCode, String)> {
+    ShortlinkService::set_link_status(
+        &state,
+        link_id,
+        user.id,
+        LINK_STATUS_ACTIVE,
+    ).await
+}
+
+/// 续期短链
+pub async fn renew_link(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(link_id): Path<u64>,
+    Json(payload): Json<LinkRenewReq>,
+) -> Result<Json<LinkRenewResp>, (StatusCode, String)> {
+    let expire_at = ShortlinkService::renew_link(
+        &state,
+        link_id,
+        user.id,
+        payload.ttl,
+    ).await?;
+
+    Ok(Json(LinkRenewResp { expire_at }))
+}
+
 /// 点击量统计（按天）
 pub async fn get_link_stats(
     State(state): State<Arc<AppState>>,
@@ -240,3 +1152,65 @@ pub async fn get_link_stats(
 
     Ok(Json(stats))
 }
+
+/// A/B 分流目标点击量统计
+pub async fn get_variant_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Query(q): Query<LinkStatsQuery>,
+) -> Result<Json<Vec<VariantStats>>, (StatusCode, String)> {
+    if let Err(e) = q.validate() {
+        warn!("get_variant_stats: 查询参数校验失败: user_id={}, error={}", user.id, e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let stats = ShortlinkService::get_variant_stats(
+        &state,
+        &q.short_code,
+        user.id,
+        &q.timezone,
+        q.days,
+    ).await?;
+
+    Ok(Json(stats))
+}
+
+/// 按标签汇总短链数和点击量
+pub async fn get_tag_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+) -> Result<Json<Vec<TagStats>>, (StatusCode, String)> {
+    let stats = ShortlinkService::tag_stats(&state, user.id).await?;
+
+    Ok(Json(stats))
+}
+
+/// 短链二维码（所有者）
+pub async fn link_qr(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Path(link_id): Path<u64>,
+    Query(q): Query<QrQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let short_url = ShortlinkService::owned_qr_url(&state, link_id, user.id).await?;
+    qr_response(&short_url, &q)
+}
+
+/// 渲染二维码图片
+fn qr_response(data: &str, q: &QrQuery) -> Result<Response, (StatusCode, String)> {
+    if let Err(e) = q.validate() {
+        warn!("qr_response: 参数校验失败: error={}", e);
+        return Err((StatusCode::BAD_REQUEST, format!("Validation error: {}", e)));
+    }
+
+    let (content_type, body) = match q.format {
+        QrFormat::Svg => ("image/svg+xml", qr::render_svg(data, q.size, q.margin).map(String::into_bytes)),
+        QrFormat::Png => ("image/png", qr::render_png(data, q.size, q.margin)),
+    };
+    let body = body.map_err(|e| {
+        warn!("qr_response: 二维码生成失败: error={}", e);
+        (StatusCode::INTERNAL_SERVER_ERROR, e)
+    })?;
+
+    Ok(([(header::CONTENT_TYPE, content_type)], body).into_response())
+}
@@ -1,9 +1,10 @@
-use axum::{extract::{ConnectInfo, State}, http::StatusCode, Json};
+use axum::{extract::{ConnectInfo, State}, http::StatusCode, Extension, Json};
 use serde::Deserialize;
 use validator::Validate;
 use std::{sync::Arc, net::SocketAddr};
 use crate::{
     state::AppState, 
+    models::user::User,
     services::{UserService, LoginResp}
 };
 use tracing::warn;
@@ -30,6 +31,14 @@ pub struct LoginPayload {
 }
 
 
+#[derive(Deserialize, Debug, Validate)]
+pub struct FallbackPayload {
+    /// 账号级过期跳转地址，null 表示清除
+    #[validate(url)]
+    pub fallback_url: Option<String>,
+}
+
+
 /// 注册
 pub async fn register(
     State(state): State<Arc<AppState>>,
@@ -75,3 +84,18 @@ pub async fn login(
 
     Ok(Json(resp))
 }
+
+
+/// 设置账号级过期跳转地址，对未单独设置的短链生效
+pub async fn set_fallback_url(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<User>,
+    Json(payload): Json<FallbackPayload>,
+) -> Result<(), (StatusCode, String)> {
+    payload.validate().map_err(|e| {
+        warn!("set_fallback_url: 参数校验失败: user_id={}, error={}", user.id, e);
+        (StatusCode::BAD_REQUEST, format!("Validation error: {}", e))
+    })?;
+
+    UserService::set_fallback_url(&state, user.id, payload.fallback_url.as_deref()).await
+}
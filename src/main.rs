@@ -1,12 +1,12 @@
 use std::{sync::Arc, net::SocketAddr, time::Duration};
 
 use axum::{
-    routing::{get, post}, 
+    routing::{get, patch, post, put}, 
     Router,
 };
 use tokio::sync::mpsc::channel;
 use dashmap::DashSet;
-use tokio::{net::TcpListener, sync::RwLock};
+use tokio::{net::TcpListener, sync::{RwLock, Semaphore}};
 use tracing_subscriber::{fmt::time::LocalTime, EnvFilter};
 use tower_http::{
     trace::{TraceLayer, DefaultMakeSpan, DefaultOnResponse},
@@ -18,13 +18,20 @@ use tracing::Level;
 use tokio_shortlink::models::db;
 use tokio_shortlink::config::AppConfig;
 use tokio_shortlink::state::AppState;
-use tokio_shortlink::handlers::{shortlink, users};
+use tokio_shortlink::handlers::{pages::ErrorPages, shortlink::{self, RedirectType}, users};
 use tokio_shortlink::middleware::{jwt_auth, ip_rate_limiter, user_rate_limiter};
 use tokio_shortlink::services::{
     spawn_click_count_sync, 
     spawn_visit_log_sync, 
     spawn_expired_links_delete,
-    background_jobs::{spawn_redis_workers, BackgroundJob},
+    spawn_expiry_reminders,
+    spawn_trash_purge,
+    spawn_url_blocklist_reload,
+    background_jobs::{spawn_metadata_workers, spawn_redis_workers, BackgroundJob, MetadataJob},
+    metadata,
+    notifier::build_notifier,
+    short_code::{build_generator, CustomCodePolicy},
+    url_policy::UrlPolicy,
 };
 
 
@@ -55,6 +62,21 @@ async fn main() {
         cfg.redis_timeout_recycle_ms,
     ).unwrap();
 
+    // 短码生成器
+    let code_generator = build_generator(&cfg).unwrap();
+    // 自定义短码校验规则
+    let code_policy = CustomCodePolicy::from_config(&cfg).unwrap();
+    // 目标地址安全策略
+    let url_policy = Arc::new(UrlPolicy::from_config(&cfg).unwrap());
+    // 错误页模板
+    let error_pages = ErrorPages::from_config(&cfg).unwrap();
+    // 到期提醒发送方式
+    let notifier = build_notifier(&cfg).unwrap();
+    // 目标页元数据抓取客户端
+    let metadata_client = metadata::build_client(cfg.link_metadata_timeout_ms, url_policy.clone()).unwrap();
+    // 默认跳转状态码
+    RedirectType::try_from(cfg.redirect_type_default).unwrap();
+
     let addr = cfg.addr.clone();
     // 全局超时层
     let timeout_layer = TimeoutLayer::new(Duration::from_millis(cfg.global_timeout_ms));
@@ -62,6 +84,11 @@ async fn main() {
     // 构建管道
     let (tx, rx) = channel::<BackgroundJob>(cfg.bg_redis_queue_cap);
     let bg_redis_max_concurrency = cfg.bg_redis_max_concurrency;
+    // 元数据抓取管道
+    let (metadata_tx, metadata_rx) = channel::<MetadataJob>(cfg.link_metadata_queue_cap);
+    let link_metadata_max_concurrency = cfg.link_metadata_max_concurrency;
+    // 后台导入任务并发数
+    let import_semaphore = Semaphore::new(cfg.import_max_concurrency);
 
     let state = Arc::new(AppState {
         mysql_pool,
@@ -69,6 +96,14 @@ async fn main() {
         bg_redis_tx: tx.clone(),
         config: RwLock::new(cfg),
         pending_set: DashSet::new(),
+        code_generator,
+        code_policy,
+        url_policy,
+        metadata_client,
+        metadata_tx,
+        notifier,
+        error_pages,
+        import_semaphore,
     });
 
     spawn_redis_workers(
@@ -76,6 +111,11 @@ async fn main() {
         rx,
         bg_redis_max_concurrency,
     );
+    spawn_metadata_workers(
+        state.clone(),
+        metadata_rx,
+        link_metadata_max_concurrency,
+    );
 
     // 启动点击量同步任务
     spawn_click_count_sync(state.clone()).await;
@@ -83,6 +123,12 @@ async fn main() {
     spawn_visit_log_sync(state.clone()).await;
     // 启动过期短链删除任务
     spawn_expired_links_delete(state.clone()).await;
+    // 启动到期提醒任务
+    spawn_expiry_reminders(state.clone()).await;
+    // 启动回收站清理任务
+    spawn_trash_purge(state.clone()).await;
+    // 启动域名黑名单重新加载任务
+    spawn_url_blocklist_reload(state.clone()).await;
 
     // Configure TraceLayer to log at INFO (defaults are DEBUG)
     let trace_layer = TraceLayer::new_for_http()
@@ -100,7 +146,9 @@ async fn main() {
     let public = Router::new()
         .route("/login", post(users::login))
         .route("/register", post(users::register))
-        .route("/s/{short_code}", get(shortlink::redirect))
+        .route("/s/{short_code}", get(shortlink::redirect).post(shortlink::redirect_with_password))
+        .route("/s/{short_code}/continue", get(shortlink::redirect_continue).post(shortlink::redirect_continue_with_password))
+        .route("/p/{short_code}", get(shortlink::preview))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(), 
             ip_rate_limiter
@@ -109,9 +157,23 @@ async fn main() {
     // 保护路由
     let protected = Router::new()
         .route("/shorten", post(shortlink::create))
+        .route("/shorten/batch", post(shortlink::create_batch))
         .route("/links", get(shortlink::list_links))
+        .route("/links/trash", get(shortlink::list_trash))
+        .route("/links/export", get(shortlink::export_links))
+        .route("/links/import", post(shortlink::import_links))
+        .route("/links/import/{job_id}", get(shortlink::import_status))
+        .route("/links/restore", post(shortlink::restore_links))
+        .route("/links/{id}", patch(shortlink::update_link))
+        .route("/links/{id}/pause", post(shortlink::pause_link))
+        .route("/links/{id}/resume", post(shortlink::resume_link))
+        .route("/links/{id}/renew", post(shortlink::renew_link))
+        .route("/links/{id}/qr", get(shortlink::link_qr))
         .route("/delete", post(shortlink::delete_links))
         .route("/stats", get(shortlink::get_link_stats))
+        .route("/stats/variants", get(shortlink::get_variant_stats))
+        .route("/tags/stats", get(shortlink::get_tag_stats))
+        .route("/account/fallback", put(users::set_fallback_url))
         .layer(axum::middleware::from_fn_with_state(
             state.clone(), 
             user_rate_limiter
@@ -2,3 +2,6 @@ pub mod db;
 pub mod link;
 pub mod user;
 pub mod session;
+pub mod idempotency;
+pub mod tag;
+pub mod import_job;
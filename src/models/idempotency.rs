@@ -0,0 +1,115 @@
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use redis::AsyncCommands;
+use deadpool_redis::Connection;
+use axum::http::StatusCode;
+use tracing::warn;
+
+
+/// 幂等记录：请求指纹 + 首次请求的响应
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    pub fingerprint: String,
+    /// 为 None 表示首次请求仍在处理中
+    pub response: Option<Value>,
+}
+
+
+pub struct Idempotency;
+
+impl Idempotency {
+    /// 抢占幂等键
+    /// 返回 None 表示抢占成功（首次请求），否则返回已存在的记录
+    pub async fn begin(
+        redis_mgr: &mut Connection,
+        key: &str,
+        fingerprint: &str,
+        ttl: i64,
+    ) -> Result<Option<IdempotencyRecord>, (StatusCode, String)> {
+        let pending = serde_json::to_string(&IdempotencyRecord {
+            fingerprint: fingerprint.to_string(),
+            response: None,
+        })
+        .map_err(|e| {
+            warn!("idempotency begin: serialize error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+        })?;
+
+        // SET NX 与 GET 之间记录可能恰好过期，最多重试一次
+        for _ in 0..2 {
+            let set: Option<String> = redis::cmd("SET")
+                .arg(key)
+                .arg(&pending)
+                .arg("NX")
+                .arg("EX")
+                .arg(ttl)
+                .query_async(redis_mgr)
+                .await
+                .map_err(|e| {
+                    warn!("idempotency begin: Redis SET NX EX error: {} key={}", e, key);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis SET NX EX error: {}", e))
+                })?;
+
+            if set.is_some() {
+                return Ok(None);
+            }
+
+            let existing: Option<String> = redis_mgr
+                .get(key)
+                .await
+                .map_err(|e| {
+                    warn!("idempotency begin: Redis get error: {} key={}", e, key);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis get error: {}", e))
+                })?;
+
+            if let Some(existing) = existing {
+                let record = serde_json::from_str(&existing).map_err(|e| {
+                    warn!("idempotency begin: deserialize error: {} key={}", e, key);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("Deserialize error: {}", e))
+                })?;
+                return Ok(Some(record));
+            }
+        }
+
+        warn!("idempotency begin: 幂等键抢占失败: key={}", key);
+        Err((StatusCode::CONFLICT, "Idempotency-Key is busy, please retry".into()))
+    }
+
+    /// 记录首次请求的响应，供重试时回放
+    pub async fn complete(
+        redis_mgr: &mut Connection,
+        key: &str,
+        fingerprint: &str,
+        response: Value,
+        ttl: i64,
+    ) -> Result<(), (StatusCode, String)> {
+        let record = serde_json::to_string(&IdempotencyRecord {
+            fingerprint: fingerprint.to_string(),
+            response: Some(response),
+        })
+        .map_err(|e| {
+            warn!("idempotency complete: serialize error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+        })?;
+
+        let _: () = redis_mgr.set_ex(key, record, ttl as u64)
+            .await
+            .map_err(|e| {
+                warn!("idempotency complete: Redis set_ex error: {} key={}", e, key);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis set_ex error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 首次请求失败时释放幂等键，允许客户端重试
+    pub async fn release(
+        redis_mgr: &mut Connection,
+        key: &str,
+    ) {
+        let result: redis::RedisResult<()> = redis_mgr.del(key).await;
+        if let Err(e) = result {
+            warn!("idempotency release: Redis del error: {} key={}", e, key);
+        }
+    }
+}
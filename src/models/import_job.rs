@@ -0,0 +1,64 @@
+use redis::AsyncCommands;
+use deadpool_redis::Connection;
+use axum::http::StatusCode;
+use tracing::warn;
+
+use crate::handlers::shortlink::ImportReport;
+
+
+/// 后台导入任务的状态与报告，保存在 Redis，按用户隔离
+pub struct ImportJob;
+
+impl ImportJob {
+    fn key(user_id: u64, job_id: &str) -> String {
+        format!("import_job:{}:{}", user_id, job_id)
+    }
+
+    /// 保存任务状态，覆盖旧记录
+    pub async fn save(
+        redis_mgr: &mut Connection,
+        user_id: u64,
+        job_id: &str,
+        report: &ImportReport,
+        ttl: i64,
+    ) -> Result<(), (StatusCode, String)> {
+        let key = Self::key(user_id, job_id);
+        let record = serde_json::to_string(report).map_err(|e| {
+            warn!("import_job save: serialize error: {} key={}", e, key);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+        })?;
+
+        let _: () = redis_mgr.set_ex(&key, record, ttl as u64)
+            .await
+            .map_err(|e| {
+                warn!("import_job save: Redis set_ex error: {} key={}", e, key);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis set_ex error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 读取任务状态，不存在或已过期时返回 None
+    pub async fn find(
+        redis_mgr: &mut Connection,
+        user_id: u64,
+        job_id: &str,
+    ) -> Result<Option<ImportReport>, (StatusCode, String)> {
+        let key = Self::key(user_id, job_id);
+        let record: Option<String> = redis_mgr
+            .get(&key)
+            .await
+            .map_err(|e| {
+                warn!("import_job find: Redis get error: {} key={}", e, key);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis get error: {}", e))
+            })?;
+
+        record
+            .map(|r| serde_json::from_str(&r))
+            .transpose()
+            .map_err(|e| {
+                warn!("import_job find: deserialize error: {} key={}", e, key);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Deserialize error: {}", e))
+            })
+    }
+}
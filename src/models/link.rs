@@ -1,22 +1,50 @@
 use tracing::warn;
-use redis::AsyncCommands;
+use redis::{AsyncCommands, Script};
 use std::collections::HashMap;
 use sqlx::{
     mysql::{MySql, MySqlDatabaseError, MySqlQueryResult}, prelude::FromRow, MySqlPool, QueryBuilder, Transaction
 };
 use deadpool_redis::Connection;
 use axum::http::StatusCode;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use chrono::{
     DateTime,
+    NaiveDate,
     NaiveDateTime,
     Utc,
     Duration,
     TimeZone,
 };
 use chrono_tz::Tz;
-use serde::{Serialize, Deserialize};
+use serde::{de::DeserializeOwned, Serialize, Deserialize};
 
-use crate::handlers::shortlink::LinkQuery;
+use crate::handlers::shortlink::{
+    LinkQuery,
+    LinkSort,
+    LinkTargets,
+    LinkVariant,
+    RedirectType,
+    SortOrder,
+    UtmTemplate,
+    VariantStats,
+};
+use crate::models::user::User;
+
+
+/// 短链状态：正常
+pub const LINK_STATUS_ACTIVE: i8 = 1;
+/// 短链状态：暂停
+pub const LINK_STATUS_PAUSED: i8 = 0;
+
+/// 访问次数额度检查结果：额度 key 不存在（需回源初始化）
+const QUOTA_MISSING: i64 = -2;
+/// 访问次数额度检查结果：额度已用完
+const QUOTA_EXHAUSTED: i64 = -1;
+
+/// 短码变更记录的保留时间（秒），需长于访问日志同步间隔
+const VISIT_LOG_RENAME_TTL: i64 = 86400;
+/// 同步访问日志时最多跟随的连续改名次数
+const VISIT_LOG_RENAME_MAX_HOPS: usize = 8;
 
 
 #[derive(Debug, Default)]
@@ -26,6 +54,7 @@ struct VisitLog {
     ip: String,
     user_agent: String,
     referer: String,
+    variant: Option<u16>,
     visit_time: String,
 }
 
@@ -37,8 +66,354 @@ pub struct LinkDto {
     pub short_code: String,
     pub long_url: String,
     pub click_count: u64,
+    pub max_clicks: Option<u32>,
+    pub status: i8,
+    pub activate_at: Option<NaiveDateTime>,
     pub expire_at: Option<NaiveDateTime>,
     pub created_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub targets: Option<String>,
+    pub variants: Option<String>,
+    pub forward_query: bool,
+    pub utm: Option<String>,
+    pub redirect_type: Option<u16>,
+    pub interstitial: bool,
+    pub qr_public: bool,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub meta_title: Option<String>,
+    pub meta_description: Option<String>,
+    pub meta_favicon: Option<String>,
+    pub fallback_url: Option<String>,
+    /// 未转换时区的创建时间，用于生成游标
+    pub created_at_utc: NaiveDateTime,
+    /// 未转换时区的过期时间，用于生成游标
+    pub expire_at_utc: Option<NaiveDateTime>,
+}
+
+
+/// 列表游标中的排序值，时间均为 UTC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "sort", content = "value", rename_all = "snake_case")]
+pub enum CursorKey {
+    CreatedAt(NaiveDateTime),
+    ClickCount(u64),
+    /// 永久短链按 permanent_expire_at() 排序
+    ExpireAt(NaiveDateTime),
+    ShortCode(String),
+}
+
+impl CursorKey {
+    pub fn sort(&self) -> LinkSort {
+        match self {
+            CursorKey::CreatedAt(_) => LinkSort::CreatedAt,
+            CursorKey::ClickCount(_) => LinkSort::ClickCount,
+            CursorKey::ExpireAt(_) => LinkSort::ExpireAt,
+            CursorKey::ShortCode(_) => LinkSort::ShortCode,
+        }
+    }
+}
+
+/// 列表游标：上一页最后一条的排序值和 id，对客户端不透明
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinkCursor {
+    pub key: CursorKey,
+    pub order: SortOrder,
+    pub id: u64,
+}
+
+impl LinkCursor {
+    fn from_row(row: &LinkDto, sort: LinkSort, order: SortOrder) -> Self {
+        let key = match sort {
+            LinkSort::CreatedAt => CursorKey::CreatedAt(row.created_at_utc),
+            LinkSort::ClickCount => CursorKey::ClickCount(row.click_count),
+            LinkSort::ExpireAt => CursorKey::ExpireAt(row.expire_at_utc.unwrap_or_else(permanent_expire_at)),
+            LinkSort::ShortCode => CursorKey::ShortCode(row.short_code.clone()),
+        };
+        Self { key, order, id: row.id }
+    }
+
+    pub fn encode(&self) -> String {
+        // 仅包含基础类型，序列化不会失败
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(raw: &str) -> Result<Self, String> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(raw)
+            .map_err(|_| "Invalid cursor".to_string())?;
+        serde_json::from_slice(&bytes).map_err(|_| "Invalid cursor".to_string())
+    }
+}
+
+/// 按过期时间排序时永久短链的排序值，与 SQL 中的 COALESCE 默认值一致
+fn permanent_expire_at() -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(9999, 12, 31)
+        .and_then(|d| d.and_hms_opt(23, 59, 59))
+        .unwrap_or(NaiveDateTime::MAX)
+}
+
+
+/// 跳转所需的短链信息（MySQL 回溯时使用）
+#[derive(Debug)]
+pub struct LinkTarget {
+    pub long_url: String,
+    pub created_at: DateTime<Utc>,
+    pub activate_at: Option<NaiveDateTime>,
+    pub expire_at: Option<NaiveDateTime>,
+    pub status: i8,
+    pub click_count: u64,
+    pub max_clicks: Option<u32>,
+    pub password_hash: Option<String>,
+    /// 按平台跳转规则 JSON
+    pub targets: Option<String>,
+    /// A/B 分流目标 JSON
+    pub variants: Option<String>,
+    pub forward_query: bool,
+    /// UTM 参数模板 JSON
+    pub utm: Option<String>,
+    /// 跳转状态码，None 表示使用配置默认值
+    pub redirect_type: Option<u16>,
+    pub interstitial: bool,
+}
+
+
+impl LinkTarget {
+    /// 回写 shortlink:{code} 缓存的内容
+    pub fn to_cached(&self) -> CachedLink {
+        CachedLink {
+            long_url: self.long_url.clone(),
+            max_clicks: self.max_clicks,
+            password_hash: self.password_hash.clone(),
+            targets: Link::parse_json(self.targets.as_deref()),
+            variants: Link::parse_json(self.variants.as_deref()),
+            forward_query: self.forward_query,
+            utm: Link::parse_json(self.utm.as_deref()),
+            redirect_type: self.redirect_type.and_then(|c| RedirectType::try_from(c).ok()),
+            interstitial: self.interstitial,
+            expire_at: self.expire_at.map(|t| t.and_utc().timestamp()),
+            created_at: Some(self.created_at.timestamp()),
+            activate_at: self.activate_at.map(|t| t.and_utc().timestamp()),
+        }
+    }
+}
+
+
+/// 即将过期、尚未提醒的短链及其所有者
+#[derive(Debug)]
+pub struct ExpiringLink {
+    pub id: u64,
+    pub user_id: u64,
+    pub email: String,
+    pub short_code: String,
+    pub long_url: String,
+    pub expire_at: NaiveDateTime,
+}
+
+
+/// 无法跳转的短链状态，用于选择错误页和过期跳转地址
+#[derive(Debug)]
+pub struct UnavailableLink {
+    pub expire_at: Option<NaiveDateTime>,
+    pub status: i8,
+    /// 短链自身的过期跳转地址，未设置时为账号默认值
+    pub fallback_url: Option<String>,
+}
+
+
+/// 开启了公开二维码的短链状态，按跳转规则判断能否展示
+#[derive(Debug)]
+pub struct PublicQrLink {
+    pub activate_at: Option<NaiveDateTime>,
+    pub status: i8,
+    pub click_count: u64,
+    pub max_clicks: Option<u32>,
+}
+
+
+/// 预览页展示的短链信息
+#[derive(Debug)]
+pub struct LinkPreview {
+    pub long_url: String,
+    pub created_at: DateTime<Utc>,
+    pub activate_at: Option<NaiveDateTime>,
+    pub expire_at: Option<NaiveDateTime>,
+    pub status: i8,
+    pub password_protected: bool,
+}
+
+
+/// 新建短链的属性
+pub struct NewLink<'a> {
+    pub long_url: &'a str,
+    /// 自定义短码，None 时自动生成
+    pub short_code: Option<&'a str>,
+    /// 生效时间，None 表示创建即生效
+    pub activate_at: Option<DateTime<Utc>>,
+    /// 过期时间，None 表示永久
+    pub expire_at: Option<DateTime<Utc>>,
+    /// 最大访问次数，None 表示不限
+    pub max_clicks: Option<u32>,
+    /// 访问密码 argon2 hash，None 表示无密码
+    pub password_hash: Option<&'a str>,
+    /// 按平台跳转规则
+    pub targets: Option<&'a LinkTargets>,
+    /// A/B 分流目标
+    pub variants: Option<&'a [LinkVariant]>,
+    /// 是否透传访问时的查询参数
+    pub forward_query: bool,
+    /// UTM 参数模板
+    pub utm: Option<&'a UtmTemplate>,
+    /// 跳转状态码，None 表示使用配置默认值
+    pub redirect_type: Option<RedirectType>,
+    /// 每次访问先展示中间页
+    pub interstitial: bool,
+    /// 允许公开获取二维码
+    pub qr_public: bool,
+    /// 标签（已规范化）
+    pub tags: &'a [String],
+    /// 标题
+    pub title: Option<&'a str>,
+    /// 备注
+    pub note: Option<&'a str>,
+    /// 过期后的跳转地址
+    pub fallback_url: Option<&'a str>,
+}
+
+
+/// 编辑短链时要更新的字段，None 表示不变
+#[derive(Default)]
+pub struct LinkChanges<'a> {
+    /// (长链, 长链 hash)
+    pub long_url: Option<(&'a str, &'a str)>,
+    /// 新有效时间（秒）
+    pub ttl: Option<i64>,
+    pub short_code: Option<&'a str>,
+    /// Some(None) 表示清除跳转规则
+    pub targets: Option<Option<&'a LinkTargets>>,
+    /// Some(None) 表示恢复配置默认值
+    pub redirect_type: Option<Option<RedirectType>>,
+    pub interstitial: Option<bool>,
+    pub qr_public: Option<bool>,
+    /// Some(None) 表示清除标题
+    pub title: Option<Option<&'a str>>,
+    /// Some(None) 表示清除备注
+    pub note: Option<Option<&'a str>>,
+    /// Some(None) 表示恢复账号默认值
+    pub fallback_url: Option<Option<&'a str>>,
+}
+
+impl LinkChanges<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.long_url.is_none()
+            && self.ttl.is_none()
+            && self.short_code.is_none()
+            && self.targets.is_none()
+            && self.redirect_type.is_none()
+            && self.interstitial.is_none()
+            && self.qr_public.is_none()
+            && self.title.is_none()
+            && self.note.is_none()
+            && self.fallback_url.is_none()
+    }
+}
+
+
+/// shortlink:{code} 缓存内容
+/// 早期版本缓存的是纯文本长链，读取时兼容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedLink {
+    pub long_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_clicks: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<LinkTargets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Vec<LinkVariant>>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forward_query: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub utm: Option<UtmTemplate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_type: Option<RedirectType>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub interstitial: bool,
+    /// 过期时间（Unix 秒），None 表示永久；用于限制永久跳转的浏览器缓存时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expire_at: Option<i64>,
+    /// 创建时间（Unix 秒），用于中间页展示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    /// 生效时间（Unix 秒），用于中间页展示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activate_at: Option<i64>,
+}
+
+impl CachedLink {
+    fn decode(raw: String) -> Self {
+        match serde_json::from_str(&raw) {
+            Ok(cached) => cached,
+            Err(_) => Self {
+                long_url: raw,
+                max_clicks: None,
+                password_hash: None,
+                targets: None,
+                variants: None,
+                forward_query: false,
+                utm: None,
+                redirect_type: None,
+                interstitial: false,
+                expire_at: None,
+                created_at: None,
+                activate_at: None,
+            },
+        }
+    }
+
+    /// 由缓存生成中间页信息，避免每次访问查询 MySQL
+    /// 早期版本的缓存没有创建时间，返回 None 由调用方回源
+    pub fn preview(&self) -> Option<LinkPreview> {
+        let to_naive = |ts: i64| DateTime::from_timestamp(ts, 0).map(|t| t.naive_utc());
+        Some(LinkPreview {
+            long_url: self.long_url.clone(),
+            created_at: DateTime::from_timestamp(self.created_at?, 0)?,
+            activate_at: self.activate_at.and_then(to_naive),
+            expire_at: self.expire_at.and_then(to_naive),
+            // 缓存中只有可跳转的短链
+            status: LINK_STATUS_ACTIVE,
+            password_protected: self.password_hash.is_some(),
+        })
+    }
+
+    /// 永久跳转允许浏览器缓存的秒数，不超过剩余有效期
+    /// 按平台跳转、A/B 分流、限制访问次数或设置了密码的短链每次访问的结果不同，不允许缓存
+    pub fn redirect_max_age(&self, max_age: u64, now_ts: i64) -> u64 {
+        if self.targets.is_some()
+            || self.variants.is_some()
+            || self.max_clicks.is_some()
+            || self.password_hash.is_some()
+        {
+            return 0;
+        }
+        match self.expire_at {
+            Some(expire_at) => max_age.min((expire_at - now_ts).max(0) as u64),
+            None => max_age,
+        }
+    }
+}
+
+
+/// 访问次数额度消费结果
+#[derive(Debug, PartialEq)]
+pub enum ClickQuota {
+    /// 消费成功
+    Granted,
+    /// 额度已用完
+    Exhausted,
+    /// 额度 key 不存在，需从 MySQL 回源初始化
+    Missing,
 }
 
 
@@ -50,32 +425,118 @@ pub struct LinkView {
     pub short_code: String,
     pub long_url: String,
     pub click_count: u64,
+    pub max_clicks: Option<u32>,
+    pub status: i8,
+    pub activate_at: Option<String>,
     pub expire_at: Option<String>,
     pub created_at: String,
+    pub deleted_at: Option<String>,
+    pub targets: Option<LinkTargets>,
+    pub variants: Option<Vec<LinkVariant>>,
+    pub forward_query: bool,
+    pub utm: Option<UtmTemplate>,
+    pub redirect_type: Option<RedirectType>,
+    pub interstitial: bool,
+    pub qr_public: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    /// 抓取的目标页 <title>
+    pub meta_title: Option<String>,
+    /// 抓取的目标页描述
+    pub meta_description: Option<String>,
+    /// 抓取的目标页图标地址
+    pub meta_favicon: Option<String>,
+    /// 过期后的跳转地址（不含账号默认值）
+    pub fallback_url: Option<String>,
 }
 
 
 pub struct Link;
 
 impl Link {
+    /// 解析跳转规则 / 分流目标等 JSON 列，内容损坏时忽略
+    fn parse_json<T: DeserializeOwned>(raw: Option<&str>) -> Option<T> {
+        raw.and_then(|raw| {
+            serde_json::from_str(raw)
+                .inspect_err(|e| warn!("parse_json: deserialize error: {}", e))
+                .ok()
+        })
+    }
+
+    /// 序列化跳转规则，空规则存为 NULL
+    fn encode_targets(targets: Option<&LinkTargets>) -> Result<Option<String>, (StatusCode, String)> {
+        targets
+            .filter(|t| !t.is_empty())
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| {
+                warn!("encode_targets: serialize error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+            })
+    }
+
     /// 插入长 URL
+    /// dedupe_hash 违反 (user_id, dedupe_hash) 唯一约束时返回 CONFLICT
     pub async fn insert_long_url(
         tx: &mut Transaction<'_, MySql>, 
-        long_url: &str,
-        expire_at: DateTime<Utc>,
+        link: &NewLink<'_>,
+        long_url_hash: &str,
+        dedupe_hash: Option<&str>,
         user_id: u64
     ) -> Result<MySqlQueryResult, (StatusCode, String)> {
+        let targets = Self::encode_targets(link.targets)?;
+        let variants = link.variants
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| {
+                warn!("insert_long_url: serialize error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+            })?;
+        let utm = link.utm
+            .filter(|u| !u.is_empty())
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| {
+                warn!("insert_long_url: serialize error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+            })?;
         let insert_sql = sqlx::query(
-            r#"INSERT INTO links (long_url, expire_at, user_id) VALUES (?, ?, ?)"#
+            r#"INSERT INTO links (long_url, long_url_hash, dedupe_hash, activate_at, expire_at, max_clicks, password_hash, targets, variants, forward_query, utm, redirect_type, interstitial, qr_public, title, note, fallback_url, user_id)
+               VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
         )
-        .bind(long_url)
-        .bind(expire_at)
+        .bind(link.long_url)
+        .bind(long_url_hash)
+        .bind(dedupe_hash)
+        .bind(link.activate_at)
+        .bind(link.expire_at)
+        .bind(link.max_clicks)
+        .bind(link.password_hash)
+        .bind(targets)
+        .bind(variants)
+        .bind(link.forward_query)
+        .bind(utm)
+        .bind(link.redirect_type.map(u16::from))
+        .bind(link.interstitial)
+        .bind(link.qr_public)
+        .bind(link.title)
+        .bind(link.note)
+        .bind(link.fallback_url)
         .bind(user_id)
         .execute(tx.as_mut())
         .await
         .map_err(
             |e| {
                 warn!("insert_long_url: DB insert error: {}", e);
+                if let sqlx::Error::Database(db_err) = &e {
+                    if let Some(mysql_err) = db_err.try_downcast_ref::<MySqlDatabaseError>() {
+                        // 1062 = Duplicate entry — 同一用户同一 URL 已有去重短链
+                        if mysql_err.number() == 1062 {
+                            return (StatusCode::CONFLICT, "Dedupe link already exists".into());
+                        }
+                    }
+                }
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("DB insert error: {}", e))
             }
         )?;
@@ -83,6 +544,62 @@ impl Link {
         Ok(insert_sql)
     }
 
+    /// 查询用户以去重方式创建、仍可复用的短码
+    /// 条件与创建时的普通短链判定一致：创建后被修改为带附加规则（跳转规则、跳转状态码、中间页等），
+    /// 或加了标题、备注、标签、过期跳转地址，以及已过期或已删除的短链不再复用
+    /// 加共享锁读取最新提交的数据，与写入冲突时持有的锁兼容，并发复用同一短链不会死锁
+    pub async fn find_short_code_by_url_hash(
+        tx: &mut Transaction<'_, MySql>,
+        user_id: u64,
+        long_url_hash: &str,
+    ) -> Result<Option<String>, (StatusCode, String)> {
+        let row = sqlx::query!(
+            r#"SELECT short_code FROM links
+               WHERE user_id = ? AND dedupe_hash = ? AND long_url_hash = ? AND short_code IS NOT NULL
+                 AND deleted_at IS NULL AND max_clicks IS NULL
+                 AND password_hash IS NULL AND targets IS NULL AND variants IS NULL
+                 AND forward_query = FALSE AND utm IS NULL AND redirect_type IS NULL
+                 AND interstitial = FALSE
+                 AND title IS NULL AND note IS NULL AND fallback_url IS NULL
+                 AND NOT EXISTS (SELECT 1 FROM link_tags lt WHERE lt.link_id = links.id)
+                 AND (activate_at IS NULL OR activate_at <= NOW())
+                 AND (expire_at IS NULL OR expire_at > NOW())
+               LOCK IN SHARE MODE"#,
+            user_id,
+            long_url_hash,
+            long_url_hash,
+        )
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("find_short_code_by_url_hash: DB select error: {} user_id={}", e, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        Ok(row.and_then(|r| r.short_code))
+    }
+
+    /// 清空不再可复用的短链的 dedupe_hash，让出唯一约束给新的去重短链
+    pub async fn release_dedupe_hash(
+        tx: &mut Transaction<'_, MySql>,
+        user_id: u64,
+        long_url_hash: &str,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"UPDATE links SET dedupe_hash = NULL WHERE user_id = ? AND dedupe_hash = ?"#,
+            user_id,
+            long_url_hash,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("release_dedupe_hash: DB update error: {} user_id={}", e, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
     /// 更新短码
     pub async fn update_short_code(
         tx: &mut Transaction<'_, MySql>, 
@@ -112,16 +629,258 @@ impl Link {
         Ok(())
     }
 
+    /// 查询用户名下未删除短链的短码
+    pub async fn find_short_code_by_id(
+        mysql_pool: &MySqlPool,
+        link_id: u64,
+        user_id: u64,
+    ) -> Result<Option<String>, (StatusCode, String)> {
+        let row = sqlx::query!(
+            r#"SELECT short_code FROM links WHERE id = ? AND user_id = ? AND deleted_at IS NULL"#,
+            link_id,
+            user_id,
+        )
+        .fetch_optional(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_short_code_by_id: DB select error: {} link_id={}", e, link_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        Ok(row.and_then(|r| r.short_code))
+    }
+
+    /// 查询允许公开获取二维码的短链（未删除、未过期且开启了 qr_public）
+    /// 生效时间、暂停和访问次数由调用方按跳转规则判断
+    pub async fn find_public_qr(
+        mysql_pool: &MySqlPool,
+        short_code: &str,
+    ) -> Result<Option<PublicQrLink>, (StatusCode, String)> {
+        sqlx::query_as!(
+            PublicQrLink,
+            r#"SELECT activate_at, status, click_count, max_clicks FROM links
+               WHERE short_code = ? AND deleted_at IS NULL AND qr_public = TRUE
+                 AND (expire_at IS NULL OR expire_at > NOW())"#,
+            short_code,
+        )
+        .fetch_optional(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_public_qr: DB select error: {} short_code={}", e, short_code);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })
+    }
+
+    /// 查询用户名下短链的短码（加锁，供编辑使用）
+    pub async fn find_owned_short_code(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        user_id: u64,
+    ) -> Result<Option<String>, (StatusCode, String)> {
+        let row = sqlx::query!(
+            r#"SELECT short_code FROM links WHERE id = ? AND user_id = ? AND deleted_at IS NULL FOR UPDATE"#,
+            link_id,
+            user_id,
+        )
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("find_owned_short_code: DB select error: {} link_id={} user_id={}", e, link_id, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        Ok(row.and_then(|r| r.short_code))
+    }
+
+    /// 编辑短链：长 URL、过期时间、短码，仅更新传入的字段
+    pub async fn update_link(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        user_id: u64,
+        changes: &LinkChanges<'_>,
+    ) -> Result<(), (StatusCode, String)> {
+        // 没有需要更新的列（如只提交了与当前相同的短码）时不生成 SQL，避免 `SET` 为空
+        if changes.is_empty() {
+            return Ok(());
+        }
+        let targets = changes.targets
+            .map(Self::encode_targets)
+            .transpose()?;
+
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new("UPDATE links SET ");
+        let mut sep = qb.separated(", ");
+        if let Some((long_url, long_url_hash)) = changes.long_url {
+            sep.push("long_url = ").push_bind_unseparated(long_url);
+            sep.push("long_url_hash = ").push_bind_unseparated(long_url_hash);
+            // 目标地址变化后旧的元数据失效，等待重新抓取
+            sep.push("meta_title = NULL, meta_description = NULL, meta_favicon = NULL, meta_fetched_at = NULL");
+        }
+        // 有效时间从当前时间与生效时间中较晚者起算
+        if let Some(ttl) = changes.ttl {
+            sep.push("expire_at = GREATEST(COALESCE(activate_at, NOW()), NOW()) + INTERVAL ")
+                .push_bind_unseparated(ttl)
+                .push_unseparated(" SECOND");
+            // 新的有效期需要重新提醒
+            sep.push("reminded_at = NULL");
+        }
+        if let Some(short_code) = changes.short_code {
+            sep.push("short_code = ").push_bind_unseparated(short_code);
+        }
+        if let Some(targets) = targets {
+            sep.push("targets = ").push_bind_unseparated(targets);
+        }
+        if let Some(redirect_type) = changes.redirect_type {
+            sep.push("redirect_type = ").push_bind_unseparated(redirect_type.map(u16::from));
+        }
+        if let Some(interstitial) = changes.interstitial {
+            sep.push("interstitial = ").push_bind_unseparated(interstitial);
+        }
+        if let Some(qr_public) = changes.qr_public {
+            sep.push("qr_public = ").push_bind_unseparated(qr_public);
+        }
+        if let Some(title) = changes.title {
+            sep.push("title = ").push_bind_unseparated(title);
+        }
+        if let Some(note) = changes.note {
+            sep.push("note = ").push_bind_unseparated(note);
+        }
+        if let Some(fallback_url) = changes.fallback_url {
+            sep.push("fallback_url = ").push_bind_unseparated(fallback_url);
+        }
+        qb.push(" WHERE id = ").push_bind(link_id)
+          .push(" AND user_id = ").push_bind(user_id);
+
+        qb.build().execute(tx.as_mut())
+            .await
+            .map_err(|e| {
+                warn!("update_link: DB update error: {} link_id={}", e, link_id);
+                if let sqlx::Error::Database(db_err) = &e {
+                    if let Some(mysql_err) = db_err.try_downcast_ref::<MySqlDatabaseError>() {
+                        // 1062 = Duplicate entry — violates UNIQUE constraint on short_code
+                        if mysql_err.number() == 1062 {
+                            return (StatusCode::CONFLICT, "Short code already exists".into());
+                        }
+                    }
+                }
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 写入抓取的目标页元数据；长链已被修改时不写入，避免覆盖为旧地址的元数据
+    pub async fn update_metadata(
+        mysql_pool: &MySqlPool,
+        short_code: &str,
+        long_url: &str,
+        title: Option<&str>,
+        description: Option<&str>,
+        favicon: Option<&str>,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"UPDATE links
+               SET meta_title = ?, meta_description = ?, meta_favicon = ?, meta_fetched_at = NOW()
+               WHERE short_code = ? AND long_url = ?"#,
+            title,
+            description,
+            favicon,
+            short_code,
+            long_url,
+        )
+        .execute(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("update_metadata: DB update error: {} short_code={}", e, short_code);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// 短码变更时迁移访问日志，保留统计数据
+    pub async fn rename_visit_logs(
+        tx: &mut Transaction<'_, MySql>,
+        old_code: &str,
+        new_code: &str,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"UPDATE visit_logs SET short_code = ? WHERE short_code = ?"#,
+            new_code,
+            old_code,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("rename_visit_logs: DB update error: {} old_code={}", e, old_code);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// 编辑后清理缓存；短码变更时把未同步的点击量和访问额度迁移到新短码
+    /// 并记录改名时间，Stream 中此前写入的旧短码访问日志在同步时归到新短码
+    pub async fn evict_shortlink(
+        redis_mgr: &mut Connection,
+        old_code: &str,
+        new_code: Option<&str>,
+    ) -> Result<(), (StatusCode, String)> {
+        let _: () = redis::cmd("UNLINK")
+            .arg(format!("shortlink:{}", old_code))
+            .query_async(redis_mgr)
+            .await
+            .map_err(|e| {
+                warn!("evict_shortlink: Redis unlink error: {} code={}", e, old_code);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis unlink error: {}", e))
+            })?;
+
+        if let Some(new_code) = new_code {
+            let script = Script::new(r#"
+                if redis.call('EXISTS', KEYS[1]) == 1 then
+                    redis.call('RENAME', KEYS[1], KEYS[2])
+                end
+                if redis.call('EXISTS', KEYS[3]) == 1 then
+                    redis.call('RENAME', KEYS[3], KEYS[4])
+                end
+                local t = redis.call('TIME')
+                local ms = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+                redis.call('SET', KEYS[5], string.format('%s %d', ARGV[1], ms), 'EX', ARGV[2])
+                return 1
+            "#);
+            let _ = script
+                .key(format!("shortlink_click:{}", old_code))
+                .key(format!("shortlink_click:{}", new_code))
+                .key(format!("shortlink_quota:{}", old_code))
+                .key(format!("shortlink_quota:{}", new_code))
+                .key(format!("visit_log_rename:{}", old_code))
+                .arg(new_code)
+                .arg(VISIT_LOG_RENAME_TTL)
+                .invoke_async::<i32>(redis_mgr)
+                .await
+                .map_err(|e| {
+                    warn!("evict_shortlink: Redis rename error: {} code={}", e, old_code);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis rename error: {}", e))
+                })?;
+        }
+
+        Ok(())
+    }
+
     /// 设置短码
     pub async fn set_shortlink(
         redis_mgr: &mut Connection,
         short_code: &str,
-        long_url: &str,
+        cached: &CachedLink,
         ttl: i64,
     ) -> Result<(), (StatusCode, String)> {
+        let value = serde_json::to_string(cached).map_err(|e| {
+            warn!("set_shortlink: serialize error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
+        })?;
+
         // 设置短链映射
         let url_key = format!("shortlink:{}", short_code);
-        let _: () = redis_mgr.set_ex(&url_key, long_url, ttl as u64)
+        let _: () = redis_mgr.set_ex(&url_key, value, ttl as u64)
             .await
             .map_err(|e| {
                 warn!("set_shortlink: Redis set_ex error: {}", e);
@@ -154,6 +913,111 @@ impl Link {
         Ok(())
     }
 
+    /// 初始化访问次数额度（已存在时不覆盖）
+    /// remaining 为扣除 MySQL 已同步点击量后的额度，脚本内再扣除 Redis 中尚未同步的点击量
+    pub async fn init_click_quota(
+        redis_mgr: &mut Connection,
+        short_code: &str,
+        remaining: i64,
+        ttl: i64,
+    ) -> Result<(), (StatusCode, String)> {
+        let script = Script::new(r#"
+            if redis.call('EXISTS', KEYS[1]) == 1 then
+                return 0
+            end
+            local pending = tonumber(redis.call('GET', KEYS[2]) or '0')
+            local remaining = tonumber(ARGV[1]) - pending
+            if remaining < 0 then
+                remaining = 0
+            end
+            redis.call('SET', KEYS[1], remaining, 'EX', ARGV[2])
+            return 1
+        "#);
+        let _ = script
+            .key(format!("shortlink_quota:{}", short_code))
+            .key(format!("shortlink_click:{}", short_code))
+            .arg(remaining)
+            .arg(ttl)
+            .invoke_async::<i32>(redis_mgr)
+            .await
+            .map_err(|e| {
+                warn!("init_click_quota: Redis eval error: {} code={}", e, short_code);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis eval error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 原子消费一次访问额度
+    pub async fn consume_click_quota(
+        redis_mgr: &mut Connection,
+        short_code: &str,
+    ) -> Result<ClickQuota, (StatusCode, String)> {
+        let script = Script::new(r#"
+            local quota = redis.call('GET', KEYS[1])
+            if not quota then
+                return -2
+            end
+            if tonumber(quota) <= 0 then
+                return -1
+            end
+            return redis.call('DECR', KEYS[1])
+        "#);
+        let result = script
+            .key(format!("shortlink_quota:{}", short_code))
+            .invoke_async::<i64>(redis_mgr)
+            .await
+            .map_err(|e| {
+                warn!("consume_click_quota: Redis eval error: {} code={}", e, short_code);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis eval error: {}", e))
+            })?;
+
+        Ok(match result {
+            QUOTA_MISSING => ClickQuota::Missing,
+            QUOTA_EXHAUSTED => ClickQuota::Exhausted,
+            _ => ClickQuota::Granted,
+        })
+    }
+
+    /// 检查访问密码错误次数是否超过限制
+    pub async fn can_try_password(
+        redis_mgr: &mut Connection,
+        fail_key: &str,
+        fail_limit: i64,
+    ) -> Result<(), (StatusCode, String)> {
+        if User::check_limit(redis_mgr, fail_key, fail_limit).await? {
+            warn!("can_try_password: 访问密码尝试被限流: fail_key={}", fail_key);
+            return Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                "Too many wrong passwords, please try again later".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 记录访问密码错误
+    pub async fn record_password_fail(
+        redis_mgr: &mut Connection,
+        fail_key: &str,
+        fail_ttl: i64,
+    ) -> Result<(), (StatusCode, String)> {
+        User::incr_count(redis_mgr, fail_key, fail_ttl).await
+    }
+
+    /// 访问密码验证成功，清除错误计数
+    pub async fn password_success(
+        redis_mgr: &mut Connection,
+        fail_key: &str,
+    ) -> Result<(), (StatusCode, String)> {
+        let _: () = redis_mgr.del(fail_key)
+            .await
+            .map_err(|e| {
+                warn!("password_success: Redis Del err: key={}, err={}", fail_key, e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis Del err: {}", e))
+            })?;
+        Ok(())
+    }
+
     /// 点击次数+1
     pub async fn in_click_count(
         redis_mgr: &mut Connection,
@@ -177,8 +1041,10 @@ impl Link {
         ip: &str,
         user_agent: &str,
         referer: &str,
+        variant: Option<u16>,
     ) {
         let now = Utc::now().to_rfc3339();
+        let variant = variant.map(|v| v.to_string()).unwrap_or_default();
         let result: redis::RedisResult<String> = redis_mgr.xadd(
             "visit_log", 
             "*", 
@@ -188,6 +1054,7 @@ impl Link {
                 ("ip", ip),
                 ("user_agent", user_agent),
                 ("referer", referer),
+                ("variant", &variant),
                 ("visit_time", &now),
             ]
         )
@@ -202,7 +1069,7 @@ impl Link {
     pub async fn get_long_url_from_redis(
         redis_mgr: &mut Connection,
         short_code: &str,
-    ) -> Result<Option<String>, (StatusCode, String)> {
+    ) -> Result<Option<CachedLink>, (StatusCode, String)> {
 
         let key = format!("shortlink:{}", short_code);
         // 从 Redis 获取映射值
@@ -214,16 +1081,46 @@ impl Link {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis get error: {}", e))
             })?;
         
-        Ok(long_url)
+        Ok(long_url.map(CachedLink::decode))
+    }
+
+    /// 查询预览页信息，只读 MySQL，不计点击
+    pub async fn find_preview(
+        mysql_pool: &MySqlPool,
+        short_code: &str,
+    ) -> Result<LinkPreview, (StatusCode, String)> {
+        let row = sqlx::query_as!(
+            LinkPreview,
+            r#"SELECT long_url, created_at, activate_at, expire_at, status,
+                      password_hash IS NOT NULL AS `password_protected: bool`
+               FROM links
+               WHERE short_code = ? AND deleted_at IS NULL"#,
+            short_code,
+        )
+        .fetch_optional(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_preview: DB select error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        row.ok_or_else(|| {
+            warn!("find_preview: 短码不存在: short_code={}", short_code);
+            (StatusCode::NOT_FOUND, "Short code not found".into())
+        })
     }
 
     /// 从 MySQL 获取长 URL
     pub async fn get_logn_url_from_mysql(
         mysql_pool: &MySqlPool,
         short_code: &str,
-    ) -> Result<(String, Option<NaiveDateTime>), (StatusCode, String)> {
-        let row = sqlx::query!(
-            r#"SELECT long_url, expire_at FROM links WHERE short_code = ?"#,
+    ) -> Result<LinkTarget, (StatusCode, String)> {
+        let row = sqlx::query_as!(
+            LinkTarget,
+            r#"SELECT long_url, created_at, activate_at, expire_at, status, click_count, max_clicks, password_hash, targets, variants,
+                      forward_query, utm, redirect_type, interstitial
+               FROM links
+               WHERE short_code = ? AND deleted_at IS NULL"#,
             short_code,
         )
         .fetch_optional(mysql_pool)
@@ -234,9 +1131,7 @@ impl Link {
         })?;
     
         match row {
-            Some(row) => {
-                Ok((row.long_url, row.expire_at))
-            },
+            Some(row) => Ok(row),
             None => {
                 warn!("get_logn_url_from_mysql: 短码不存在: short_code={}", short_code);
                 Err((StatusCode::NOT_FOUND, "Short code not found".into()))
@@ -244,6 +1139,96 @@ impl Link {
         }
     }
 
+    /// 查询短链状态及过期跳转地址（跳转失败后使用，不走缓存）
+    pub async fn find_unavailable(
+        mysql_pool: &MySqlPool,
+        short_code: &str,
+    ) -> Result<Option<UnavailableLink>, (StatusCode, String)> {
+        sqlx::query_as!(
+            UnavailableLink,
+            r#"SELECT l.expire_at, l.status, COALESCE(l.fallback_url, u.fallback_url) AS fallback_url
+               FROM links l
+               JOIN users u ON u.id = l.user_id
+               WHERE l.short_code = ? AND l.deleted_at IS NULL"#,
+            short_code,
+        )
+        .fetch_optional(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_unavailable: DB select error: {} short_code={}", e, short_code);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })
+    }
+
+    /// 暂停 / 恢复短链
+    pub async fn set_status(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        user_id: u64,
+        status: i8,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"UPDATE links
+               SET status = ?, disabled_at = CASE WHEN ? = 1 THEN NULL ELSE NOW() END
+               WHERE id = ? AND user_id = ?"#,
+            status,
+            status,
+            link_id,
+            user_id,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("set_status: DB update error: {} link_id={} status={}", e, link_id, status);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
+    /// 查询自己名下短链的短码和过期时间（加行锁）
+    pub async fn find_owned_expiry(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        user_id: u64,
+    ) -> Result<Option<(String, Option<NaiveDateTime>)>, (StatusCode, String)> {
+        let row = sqlx::query!(
+            r#"SELECT short_code, expire_at FROM links
+               WHERE id = ? AND user_id = ? AND deleted_at IS NULL FOR UPDATE"#,
+            link_id,
+            user_id,
+        )
+        .fetch_optional(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("find_owned_expiry: DB select error: {} link_id={} user_id={}", e, link_id, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        Ok(row.and_then(|r| r.short_code.map(|code| (code, r.expire_at))))
+    }
+
+    /// 续期：写入新的过期时间，并清空提醒时间
+    pub async fn renew(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        expire_at: NaiveDateTime,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"UPDATE links SET expire_at = ?, reminded_at = NULL WHERE id = ?"#,
+            expire_at,
+            link_id,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("renew: DB update error: {} link_id={}", e, link_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
     /// 同步点击量
     pub async fn sync_click_counts(
         mysql_pool: &MySqlPool,
@@ -325,11 +1310,14 @@ impl Link {
     }
     
     /// 同步访问日志
+    /// 短码变更前写入 Stream 的日志按变更记录写到新短码下
     pub async fn sync_visit_logs(
         mysql_pool: &MySqlPool,
         redis_mgr: &mut Connection,
         batch: usize,
     ) -> Result<(), (StatusCode, String)> {
+        // 本次同步中已查询过的短码变更记录
+        let mut renames = HashMap::new();
         loop {
             // 1. 从 Stream 读出一批记录（XRANGE visit_log - + COUNT batch）
             //    返回值形如 Vec<(id, Vec<(field, value)>)>
@@ -362,21 +1350,29 @@ impl Link {
                         "ip"          => visit_log.ip          = value,
                         "user_agent"  => visit_log.user_agent  = value,
                         "referer"     => visit_log.referer     = value,
+                        "variant"     => visit_log.variant     = value.parse().ok(),
                         "visit_time"  => visit_log.visit_time  = value,
                         _ => {}
                     }
                 }
+                visit_log.short_code = Self::resolve_renamed_code(
+                    redis_mgr,
+                    &mut renames,
+                    visit_log.short_code,
+                    &entry_id,
+                ).await?;
 
                 // 3. 写入 MySQL
                 sqlx::query!(
                     r#"INSERT INTO visit_logs
-                       (short_code, long_url, ip, user_agent, referer, visit_time)
-                       VALUES (?, ?, ?, ?, ?, ?)"#,
+                       (short_code, long_url, ip, user_agent, referer, variant, visit_time)
+                       VALUES (?, ?, ?, ?, ?, ?, ?)"#,
                     visit_log.short_code,
                     visit_log.long_url,
                     visit_log.ip,
                     visit_log.user_agent,
                     visit_log.referer,
+                    visit_log.variant,
                     visit_log.visit_time,
                 )
                 .execute(mysql_pool)
@@ -402,6 +1398,45 @@ impl Link {
         Ok(())
     }
 
+    /// 短码变更后，Stream 中改名前写入的旧短码日志归到当前短码
+    /// entry_id 为 Stream 条目编号（毫秒时间戳-序号），连续改名时逐次跟随
+    async fn resolve_renamed_code(
+        redis_mgr: &mut Connection,
+        renames: &mut HashMap<String, Option<(String, i64)>>,
+        short_code: String,
+        entry_id: &str,
+    ) -> Result<String, (StatusCode, String)> {
+        let entry_ms = entry_id
+            .split('-')
+            .next()
+            .and_then(|ms| ms.parse::<i64>().ok())
+            .unwrap_or(i64::MAX);
+
+        let mut code = short_code;
+        for _ in 0..VISIT_LOG_RENAME_MAX_HOPS {
+            if !renames.contains_key(&code) {
+                let value: Option<String> = redis_mgr
+                    .get(format!("visit_log_rename:{}", code))
+                    .await
+                    .map_err(|e| {
+                        warn!("sync_visit_logs: Redis get rename error: {} code={}", e, code);
+                        (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis get error: {}", e))
+                    })?;
+                let rename = value.and_then(|v| {
+                    let (new_code, ms) = v.split_once(' ')?;
+                    Some((new_code.to_string(), ms.parse().ok()?))
+                });
+                renames.insert(code.clone(), rename);
+            }
+            match &renames[&code] {
+                Some((new_code, renamed_ms)) if entry_ms <= *renamed_ms => code = new_code.clone(),
+                _ => break,
+            }
+        }
+
+        Ok(code)
+    }
+
     /// 拼接 SQL 查询
     fn apply_filters<'a>(
         qb: &mut QueryBuilder<'a, MySql>,
@@ -422,6 +1457,12 @@ impl Link {
             qb.push(" AND click_count = ").push_bind(click_count);
         }
 
+        if let Some(tag) = filter.tag.as_deref() {
+            qb.push(" AND id IN (SELECT lt.link_id FROM link_tags lt JOIN tags t ON t.id = lt.tag_id WHERE t.name = ")
+                .push_bind(tag.trim())
+                .push(")");
+        }
+
         if let Some(date_from) = filter.date_from {
             qb.push(" AND created_at >= ").push_bind(date_from);
         }
@@ -432,6 +1473,31 @@ impl Link {
 
         // 只查询未过期的短链（expire_at 为 NULL 或大于当前时间）
         qb.push(" AND (expire_at IS NULL OR expire_at > NOW())");
+
+        // 排除回收站中的短链
+        qb.push(" AND deleted_at IS NULL");
+    }
+
+    /// 列表查询的 SELECT 部分，时间列转换为客户端时区
+    fn select_links(timezone: &str) -> QueryBuilder<'_, MySql> {
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new(
+            "SELECT id, user_id, short_code, long_url, click_count, max_clicks, status, "
+        );
+        qb.push("CONVERT_TZ(activate_at, 'UTC', ")
+            .push_bind(timezone)
+            .push(") AS activate_at, ")
+            .push("CONVERT_TZ(expire_at, 'UTC', ")
+            .push_bind(timezone)
+            .push(") AS expire_at, ")
+            .push("CONVERT_TZ(created_at, 'UTC', ")
+            .push_bind(timezone)
+            .push(") AS created_at, ")
+            .push("CONVERT_TZ(deleted_at, 'UTC', ")
+            .push_bind(timezone)
+            .push(") AS deleted_at, targets, variants, forward_query, utm, redirect_type, interstitial, qr_public, ")
+            .push("title, note, meta_title, meta_description, meta_favicon, fallback_url, ")
+            .push("created_at AS created_at_utc, expire_at AS expire_at_utc FROM links WHERE 1 = 1 ");
+        qb
     }
 
     /// 构建返回数据
@@ -443,41 +1509,68 @@ impl Link {
             short_code: src.short_code,
             long_url: src.long_url,
             click_count: src.click_count,
+            max_clicks: src.max_clicks,
+            status: src.status,
+            activate_at: src.activate_at.map(|t| t.format(fmt).to_string()),
             expire_at: src.expire_at.map(|t| t.format(fmt).to_string()),
             created_at: src.created_at.format(fmt).to_string(),
+            deleted_at: src.deleted_at.map(|t| t.format(fmt).to_string()),
+            targets: Self::parse_json(src.targets.as_deref()),
+            variants: Self::parse_json(src.variants.as_deref()),
+            forward_query: src.forward_query,
+            utm: Self::parse_json(src.utm.as_deref()),
+            redirect_type: src.redirect_type.and_then(|c| RedirectType::try_from(c).ok()),
+            interstitial: src.interstitial,
+            qr_public: src.qr_public,
+            tags: Vec::new(),
+            title: src.title,
+            note: src.note,
+            meta_title: src.meta_title,
+            meta_description: src.meta_description,
+            meta_favicon: src.meta_favicon,
+            fallback_url: src.fallback_url,
         }
     }
 
-    /// 查询短链列表
+    /// 查询短链列表，返回本页数据和下一页游标（没有更多数据时为 None）
+    /// 传入游标时从游标位置继续读取并忽略 offset
     pub async fn find_links(
         mysql_pool: &MySqlPool,
         filter: &LinkQuery,
+        cursor: Option<&LinkCursor>,
         limit: u64,
         offset: u64,
-    ) -> Result<(Vec<LinkView>, i64), (StatusCode, String)> {
+    ) -> Result<(Vec<LinkView>, Option<LinkCursor>), (StatusCode, String)> {
 
-        let mut data_qb: QueryBuilder<MySql> = QueryBuilder::new(
-            "SELECT id, user_id, short_code, long_url, click_count, "
-        );
-        data_qb
-            .push("CONVERT_TZ(expire_at, 'UTC', ")
-            .push_bind(&filter.timezone)
-            .push(") AS expire_at, ")
-            .push("CONVERT_TZ(created_at, 'UTC', ")
-            .push_bind(&filter.timezone)
-            .push(") AS created_at FROM links WHERE 1 = 1 ");
+        let mut data_qb = Self::select_links(&filter.timezone);
 
         // 添加筛选条件
         Self::apply_filters(&mut data_qb, filter);
 
-        // 分页 & 排序
-        data_qb.push(" ORDER BY created_at DESC LIMIT ")
-            .push_bind(limit)
-            .push(" OFFSET ")
-            .push_bind(offset);
+        // 排序值相同时按 id 排序，保证游标位置唯一
+        let column = Self::sort_column(filter.sort);
+        let direction = match filter.order {
+            SortOrder::Asc => " ASC",
+            SortOrder::Desc => " DESC",
+        };
+        if let Some(cursor) = cursor {
+            Self::push_cursor(&mut data_qb, column, cursor);
+        }
+
+        // 多取一条判断是否还有下一页
+        data_qb.push(" ORDER BY ")
+            .push(column)
+            .push(direction)
+            .push(", id")
+            .push(direction)
+            .push(" LIMIT ")
+            .push_bind(limit + 1);
+        if cursor.is_none() {
+            data_qb.push(" OFFSET ").push_bind(offset);
+        }
 
         // 编译执行
-        let rows = data_qb.build_query_as::<LinkDto>()
+        let mut rows = data_qb.build_query_as::<LinkDto>()
             .fetch_all(mysql_pool)
             .await
             .map_err(|e| {
@@ -485,28 +1578,101 @@ impl Link {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
             })?;
 
+        let next_cursor = if rows.len() as u64 > limit {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| LinkCursor::from_row(row, filter.sort, filter.order))
+        } else {
+            None
+        };
+
         let items = rows
             .into_iter()
             .map(Self::to_view)
             .collect();
 
-        // 统计总数
+        Ok((items, next_cursor))
+    }
+
+    /// 统计满足筛选条件的短链总数
+    pub async fn count_links(
+        mysql_pool: &MySqlPool,
+        filter: &LinkQuery,
+    ) -> Result<i64, (StatusCode, String)> {
         let mut count_qb: QueryBuilder<MySql> = QueryBuilder::new(
             "SELECT COUNT(*) FROM links WHERE 1 = 1 "
         );
         Self::apply_filters(&mut count_qb, filter);
-        let count: i64 = count_qb.build_query_scalar()
+        count_qb.build_query_scalar()
             .fetch_one(mysql_pool)
             .await
             .map_err(|e| {
-                warn!("find_links: DB select error (count): {}", e);
+                warn!("count_links: DB select error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+            })
+    }
+
+    /// 排序列；永久短链按过期时间排序时排在最晚
+    fn sort_column(sort: LinkSort) -> &'static str {
+        match sort {
+            LinkSort::CreatedAt => "created_at",
+            LinkSort::ClickCount => "click_count",
+            LinkSort::ExpireAt => "COALESCE(expire_at, '9999-12-31 23:59:59')",
+            LinkSort::ShortCode => "short_code",
+        }
+    }
+
+    /// 游标条件：排序值在游标之后，或排序值相同且 id 在游标之后
+    fn push_cursor<'a>(
+        qb: &mut QueryBuilder<'a, MySql>,
+        column: &str,
+        cursor: &'a LinkCursor,
+    ) {
+        let op = match cursor.order {
+            SortOrder::Asc => " > ",
+            SortOrder::Desc => " < ",
+        };
+        qb.push(" AND (").push(column).push(op);
+        Self::push_cursor_value(qb, &cursor.key);
+        qb.push(" OR (").push(column).push(" = ");
+        Self::push_cursor_value(qb, &cursor.key);
+        qb.push(" AND id").push(op).push_bind(cursor.id).push("))");
+    }
+
+    fn push_cursor_value<'a>(qb: &mut QueryBuilder<'a, MySql>, key: &'a CursorKey) {
+        match key {
+            CursorKey::CreatedAt(t) | CursorKey::ExpireAt(t) => qb.push_bind(*t),
+            CursorKey::ClickCount(c) => qb.push_bind(*c),
+            CursorKey::ShortCode(s) => qb.push_bind(s.as_str()),
+        };
+    }
+
+    /// 按 id 升序分批读取 id 大于 after_id 的短链，用于导出
+    pub async fn find_links_after(
+        mysql_pool: &MySqlPool,
+        filter: &LinkQuery,
+        after_id: u64,
+        limit: u64,
+    ) -> Result<Vec<LinkView>, (StatusCode, String)> {
+        let mut qb = Self::select_links(&filter.timezone);
+        Self::apply_filters(&mut qb, filter);
+        qb.push(" AND id > ")
+            .push_bind(after_id)
+            .push(" ORDER BY id LIMIT ")
+            .push_bind(limit);
+
+        let rows = qb.build_query_as::<LinkDto>()
+            .fetch_all(mysql_pool)
+            .await
+            .map_err(|e| {
+                warn!("find_links_after: DB select error: {} after_id={}", e, after_id);
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
             })?;
 
-        Ok((items, count))
+        Ok(rows.into_iter().map(Self::to_view).collect())
     }
 
     /// 删除短链(手动)
+    /// 软删除：写入 deleted_at 进入回收站，宽限期内可恢复，短码在物理删除前不会被复用
     pub async fn delete_links(
         tx: &mut Transaction<'_, MySql>,
         redis_mgr: &mut Connection,
@@ -518,7 +1684,7 @@ impl Link {
             "SELECT short_code FROM links WHERE user_id = "
         );
         code_qb.push_bind(user_id)
-              .push(" AND id IN (");
+              .push(" AND deleted_at IS NULL AND id IN (");
         let mut sep = code_qb.separated(", ");
         for id in link_ids {
             sep.push_bind(id);
@@ -539,52 +1705,31 @@ impl Link {
             )?;
 
         if !short_codes.is_empty() {
-            // todo: 是否直接物理删除？visit_log 表中的记录是否需要保留(保留短链不会被回收)？
-            // 暂时先直接删除
-            // 构造并执行批量 DELETE
-            let mut qb = QueryBuilder::new("DELETE FROM links WHERE id IN ( ");
+            // 构造并执行批量软删除；visit_logs 保留到物理删除时一并清理
+            let mut qb = QueryBuilder::new("UPDATE links SET deleted_at = NOW() WHERE id IN ( ");
             let mut separated = qb.separated(", ");
             for link_id in link_ids {
                 separated.push_bind(link_id);
             }
-            qb.push(") AND user_id = ").push_bind(user_id);
-            qb.build().execute(tx.as_mut())
-                .await
-                .map_err(
-                    |e| {
-                        warn!("delete_links: DB Delete error: {}", e);
-                        (
-                            StatusCode::INTERNAL_SERVER_ERROR, 
-                            format!("DB Delete error: {}", e)
-                        )
-                    }
-                )?;
-            
-            // 将visit_log表中对应的短链删除
-            let mut qb = QueryBuilder::new("DELETE FROM visit_logs WHERE short_code IN ( ");
-            let mut separated = qb.separated(", ");
-            for (short_code,) in &short_codes {
-                separated.push_bind(short_code);
-            }
-            qb.push(")");
+            qb.push(") AND user_id = ").push_bind(user_id)
+              .push(" AND deleted_at IS NULL");
             qb.build().execute(tx.as_mut())
                 .await
                 .map_err(
                     |e| {
-                        warn!("delete_links: DB Delete error: {}", e);
+                        warn!("delete_links: DB Update error: {}", e);
                         (
                             StatusCode::INTERNAL_SERVER_ERROR, 
-                            format!("DB Delete error: {}", e)
+                            format!("DB Update error: {}", e)
                         )
                     }
                 )?;
 
-            // 构造并执行批量 UNLINK
+            // 构造并执行批量 UNLINK；点击量计数保留，恢复后统计不丢失
             let mut pipe = redis::pipe();
             pipe.atomic();
             for (code,) in &short_codes {
                 pipe.cmd("UNLINK").arg(format!("shortlink:{}", code)).ignore();
-                pipe.cmd("UNLINK").arg(format!("shortlink_click:{}", code)).ignore();
             }
             let _: () = pipe.query_async(redis_mgr)
                 .await
@@ -597,14 +1742,238 @@ impl Link {
         Ok(())
     }
 
+    /// 恢复回收站中的短链
+    pub async fn restore_links(
+        mysql_pool: &MySqlPool,
+        link_ids: &[u64],
+        user_id: u64,
+    ) -> Result<u64, (StatusCode, String)> {
+        let mut qb = QueryBuilder::new("UPDATE links SET deleted_at = NULL WHERE id IN ( ");
+        let mut separated = qb.separated(", ");
+        for link_id in link_ids {
+            separated.push_bind(link_id);
+        }
+        qb.push(") AND user_id = ").push_bind(user_id)
+          .push(" AND deleted_at IS NOT NULL");
+        let result = qb.build().execute(mysql_pool)
+            .await
+            .map_err(|e| {
+                warn!("restore_links: DB Update error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Update error: {}", e))
+            })?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 查询回收站
+    pub async fn find_trash(
+        mysql_pool: &MySqlPool,
+        user_id: u64,
+        timezone: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<LinkView>, i64), (StatusCode, String)> {
+        let mut data_qb = Self::select_links(timezone);
+        data_qb
+            .push(" AND deleted_at IS NOT NULL AND user_id = ")
+            .push_bind(user_id)
+            .push(" ORDER BY deleted_at DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let rows = data_qb.build_query_as::<LinkDto>()
+            .fetch_all(mysql_pool)
+            .await
+            .map_err(|e| {
+                warn!("find_trash: DB select error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+            })?;
+
+        let items = rows
+            .into_iter()
+            .map(Self::to_view)
+            .collect();
+
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) FROM links WHERE deleted_at IS NOT NULL AND user_id = ?"#,
+            user_id,
+        )
+        .fetch_one(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_trash: DB select error (count): {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        Ok((items, count))
+    }
+
+    /// 查询 hours 小时内过期且尚未提醒的短链（不含回收站），按过期时间排序
+    pub async fn find_expiring_links(
+        mysql_pool: &MySqlPool,
+        hours: i64,
+        limit: u64,
+    ) -> Result<Vec<ExpiringLink>, (StatusCode, String)> {
+        sqlx::query_as!(
+            ExpiringLink,
+            r#"SELECT l.id, l.user_id, u.email, l.short_code AS `short_code!`, l.long_url,
+                      l.expire_at AS `expire_at!`
+               FROM links l
+               JOIN users u ON u.id = l.user_id
+               WHERE l.expire_at > NOW()
+                 AND l.expire_at <= NOW() + INTERVAL ? HOUR
+                 AND l.reminded_at IS NULL
+                 AND l.deleted_at IS NULL
+                 AND l.short_code IS NOT NULL
+               ORDER BY l.expire_at
+               LIMIT ?"#,
+            hours,
+            limit,
+        )
+        .fetch_all(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("find_expiring_links: DB select error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })
+    }
+
+    /// 标记已发送到期提醒
+    pub async fn mark_reminded(
+        mysql_pool: &MySqlPool,
+        link_ids: &[u64],
+    ) -> Result<(), (StatusCode, String)> {
+        if link_ids.is_empty() {
+            return Ok(());
+        }
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new(
+            "UPDATE links SET reminded_at = NOW() WHERE id IN ("
+        );
+        let mut sep = qb.separated(", ");
+        for id in link_ids {
+            sep.push_bind(*id);
+        }
+        qb.push(")");
+        qb.build().execute(mysql_pool)
+            .await
+            .map_err(|e| {
+                warn!("mark_reminded: DB update error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 物理删除超过宽限期的回收站短链(定时任务)
+    /// 同时清理对应的 visit_logs 与 Redis 点击量计数
+    pub async fn purge_deleted_links(
+        mysql_pool: &MySqlPool,
+        redis_mgr: &mut Connection,
+        grace_secs: i64,
+        batch: usize,
+    ) -> Result<(), (StatusCode, String)> {
+        loop {
+            let mut tx = mysql_pool.begin().await.map_err(|e| {
+                warn!("purge_deleted_links: DB Begin error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Begin error: {}", e))
+            })?;
+
+            let rows: Vec<(u64, Option<String>)> = sqlx::query_as(
+                r#"SELECT id, short_code FROM links
+                   WHERE deleted_at < NOW() - INTERVAL ? SECOND
+                   ORDER BY id LIMIT ? FOR UPDATE"#
+            )
+            .bind(grace_secs)
+            .bind(batch as u64)
+            .fetch_all(tx.as_mut())
+            .await
+            .map_err(|e| {
+                warn!("purge_deleted_links: DB select error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+            })?;
+
+            if rows.is_empty() {
+                break;
+            }
+
+            let short_codes: Vec<&String> = rows.iter().filter_map(|(_, c)| c.as_ref()).collect();
+
+            if !short_codes.is_empty() {
+                // 将visit_log表中对应的短链删除
+                let mut qb = QueryBuilder::new("DELETE FROM visit_logs WHERE short_code IN ( ");
+                let mut separated = qb.separated(", ");
+                for short_code in &short_codes {
+                    separated.push_bind(*short_code);
+                }
+                qb.push(")");
+                qb.build().execute(tx.as_mut())
+                    .await
+                    .map_err(|e| {
+                        warn!("purge_deleted_links: DB Delete error: {}", e);
+                        (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Delete error: {}", e))
+                    })?;
+            }
+
+            let mut qb = QueryBuilder::new("DELETE FROM links WHERE id IN ( ");
+            let mut separated = qb.separated(", ");
+            for (id, _) in &rows {
+                separated.push_bind(id);
+            }
+            qb.push(")");
+            qb.build().execute(tx.as_mut())
+                .await
+                .map_err(|e| {
+                    warn!("purge_deleted_links: DB Delete error: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Delete error: {}", e))
+                })?;
+
+            tx.commit().await.map_err(|e| {
+                warn!("purge_deleted_links: DB Commit error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+            })?;
+
+            if !short_codes.is_empty() {
+                let mut pipe = redis::pipe();
+                for code in &short_codes {
+                    pipe.cmd("UNLINK").arg(format!("shortlink:{}", code)).ignore();
+                    pipe.cmd("UNLINK").arg(format!("shortlink_click:{}", code)).ignore();
+                    pipe.cmd("UNLINK").arg(format!("shortlink_quota:{}", code)).ignore();
+                }
+                let _: () = pipe.query_async(redis_mgr)
+                    .await
+                    .map_err(|e| {
+                        warn!("purge_deleted_links: Redis unlink error: {}", e);
+                        (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis unlink error: {}", e))
+                    })?;
+            }
+
+            if rows.len() < batch {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
     /// 过期短链删除(定时任务)
+    /// 设置了过期跳转地址（短链级或账号级）的过期短链再保留 fallback_retention_days 天，期间访问仍跳转到该地址
+    /// 回收站中的短链由回收站清理任务按保留时长处理，这里不删除
     pub async fn delete_expired_links(
         mysql_pool: &MySqlPool,
+        fallback_retention_days: i64,
     ) -> Result<(), (StatusCode, String)> {
         // 构造并执行批量 DELETE
         let mut qb = QueryBuilder::new(
-            "DELETE FROM links WHERE expire_at < NOW()"
+            r#"DELETE l FROM links l
+               JOIN users u ON u.id = l.user_id
+               WHERE l.deleted_at IS NULL
+                 AND l.expire_at < NOW()
+                 AND ((l.fallback_url IS NULL AND u.fallback_url IS NULL)
+                      OR l.expire_at < NOW() - INTERVAL "#
         );
+        qb.push_bind(fallback_retention_days);
+        qb.push(" DAY)");
         qb.build().execute(mysql_pool)
             .await
             .map_err(
@@ -620,6 +1989,33 @@ impl Link {
         Ok(())
     }
 
+    /// 统计窗口：返回 (本地起始 00:00:00, 本地当前时间)，days 天含当天
+    fn stats_window(
+        timezone: &str,
+        days: u8,
+    ) -> Result<(DateTime<Tz>, DateTime<Tz>), (StatusCode, String)> {
+        let tz: Tz = timezone.parse().map_err(|_| {
+            warn!("stats_window: invalid timezone: {}", timezone);
+            (StatusCode::BAD_REQUEST, "Invalid timezone".to_string())
+        })?;
+        let now_local = Utc::now().with_timezone(&tz);
+
+        // 本地起始 00:00:00（days 天 含当天）
+        let start_local_midnight = (now_local - Duration::days(days as i64 - 1))
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let start_local_dt = tz
+            .from_local_datetime(&start_local_midnight)
+            .single()
+            .ok_or_else(|| {
+                warn!("stats_window: ambiguous local datetime for start_midnight");
+                (StatusCode::INTERNAL_SERVER_ERROR, "Ambiguous local datetime".to_string())
+            })?;
+
+        Ok((start_local_dt, now_local))
+    }
+
     /// 点击量统计（按天）
     /// 返回一个按日期升序排列的 `(yyyy-mm-dd, 点击量)` 列表
     pub async fn count_daily_visits_by_code(
@@ -631,7 +2027,7 @@ impl Link {
     ) -> Result<Vec<(String, i64)>, (StatusCode, String)> {
         // 校验短链归属
         let row = sqlx::query!(
-            r#"SELECT id FROM links WHERE short_code = ? AND user_id = ?"#,
+            r#"SELECT id FROM links WHERE short_code = ? AND user_id = ? AND deleted_at IS NULL"#,
             short_code,
             user_id,
         )
@@ -651,25 +2047,8 @@ impl Link {
 
 
         // 计算 UTC 查询范围
-        let tz: Tz = timezone.parse().map_err(|_| {
-            warn!("count_daily_visits_by_code: invalid timezone: {}", timezone);
-            (StatusCode::BAD_REQUEST, "Invalid timezone".to_string())
-        })?;
-        let now_utc = Utc::now();
-        let now_local = now_utc.with_timezone(&tz);
-
-        // 本地起始 00:00:00（days 天 含当天）
-        let start_local_midnight = (now_local - Duration::days(days as i64 - 1))
-            .date_naive()
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
-        let start_local_dt = tz
-            .from_local_datetime(&start_local_midnight)
-            .single()
-            .ok_or_else(|| {
-                warn!("count_daily_visits_by_code: ambiguous local datetime for start_midnight");
-                (StatusCode::INTERNAL_SERVER_ERROR, "Ambiguous local datetime".to_string())
-            })?;
+        let (start_local_dt, now_local) = Self::stats_window(&timezone, days)?;
+        let now_utc = now_local.with_timezone(&Utc);
         let start_utc = start_local_dt.naive_utc();
     
         let today_local = now_local.date_naive();
@@ -717,4 +2096,142 @@ impl Link {
 
         Ok(result)
     }
-}
\ No newline at end of file
+
+    /// A/B 分流目标点击量统计
+    /// 按创建时的分流目标顺序返回，未配置分流的短链返回空列表
+    pub async fn count_visits_by_variant(
+        mysql_pool: &MySqlPool,
+        short_code: &str,
+        timezone: &str,
+        user_id: u64,
+        days: u8,
+    ) -> Result<Vec<VariantStats>, (StatusCode, String)> {
+        // 校验短链归属
+        let row = sqlx::query!(
+            r#"SELECT variants FROM links WHERE short_code = ? AND user_id = ? AND deleted_at IS NULL"#,
+            short_code,
+            user_id,
+        )
+        .fetch_optional(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("count_visits_by_variant: DB select error: {} short_code={} user_id={}", e, short_code, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?
+        .ok_or_else(|| {
+            warn!("count_visits_by_variant: 短码不存在: short_code={} user_id={}", short_code, user_id);
+            (StatusCode::NOT_FOUND, "Short code not found".to_string())
+        })?;
+
+        let variants: Vec<LinkVariant> = Self::parse_json(row.variants.as_deref()).unwrap_or_default();
+        if variants.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (start_local_dt, now_local) = Self::stats_window(timezone, days)?;
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT variant AS `variant!: u16`, COUNT(*) AS cnt
+            FROM visit_logs
+            WHERE short_code = ? AND variant IS NOT NULL AND visit_time >= ? AND visit_time <= ?
+            GROUP BY variant
+            "#,
+            short_code,
+            start_local_dt.naive_utc(),
+            now_local.naive_utc(),
+        )
+        .fetch_all(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("count_visits_by_variant: DB select error (visit_logs): {} short_code={}", e, short_code);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })?;
+
+        let counts: HashMap<u16, i64> = rows
+            .into_iter()
+            .map(|row| (row.variant, row.cnt))
+            .collect();
+
+        Ok(variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let variant = i as u16;
+                VariantStats {
+                    variant,
+                    url: v.url,
+                    weight: v.weight,
+                    clicks: counts.get(&variant).copied().unwrap_or(0),
+                }
+            })
+            .collect())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_cursor() {
+        let created_at = NaiveDate::from_ymd_opt(2026, 10, 15)
+            .and_then(|d| d.and_hms_opt(8, 30, 0))
+            .unwrap();
+        for key in [
+            CursorKey::CreatedAt(created_at),
+            CursorKey::ClickCount(42),
+            CursorKey::ExpireAt(permanent_expire_at()),
+            CursorKey::ShortCode("abc-1".into()),
+        ] {
+            let cursor = LinkCursor { key, order: SortOrder::Asc, id: 7 };
+            let encoded = cursor.encode();
+            // URL 安全，可直接放进查询参数
+            assert!(encoded.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+            assert_eq!(LinkCursor::decode(&encoded).unwrap(), cursor);
+        }
+
+        assert_eq!(CursorKey::ShortCode("x".into()).sort(), LinkSort::ShortCode);
+        assert!(LinkCursor::decode("not a cursor").is_err());
+        assert!(LinkCursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"id\":1}")).is_err());
+    }
+
+    #[test]
+    fn test_redirect_max_age() {
+        let mut cached = CachedLink::decode("https://www.example.com".into());
+        assert_eq!(cached.redirect_max_age(86400, 1000), 86400);
+
+        // 不超过剩余有效期，已过期时为 0
+        cached.expire_at = Some(1600);
+        assert_eq!(cached.redirect_max_age(86400, 1000), 600);
+        assert_eq!(cached.redirect_max_age(300, 1000), 300);
+        assert_eq!(cached.redirect_max_age(86400, 2000), 0);
+
+        // 每次访问结果可能不同的短链不缓存
+        cached.expire_at = None;
+        cached.max_clicks = Some(3);
+        assert_eq!(cached.redirect_max_age(86400, 1000), 0);
+        cached.max_clicks = None;
+        cached.variants = Some(Vec::new());
+        assert_eq!(cached.redirect_max_age(86400, 1000), 0);
+    }
+
+    #[test]
+    fn test_cached_preview() {
+        // 早期缓存没有创建时间
+        let mut cached = CachedLink::decode("https://www.example.com".into());
+        assert!(cached.preview().is_none());
+
+        cached.created_at = Some(1000);
+        cached.expire_at = Some(2000);
+        cached.password_hash = Some("hash".into());
+        let preview = cached.preview().unwrap();
+        assert_eq!(preview.long_url, "https://www.example.com");
+        assert_eq!(preview.created_at.timestamp(), 1000);
+        assert_eq!(preview.activate_at, None);
+        assert_eq!(preview.expire_at.unwrap().and_utc().timestamp(), 2000);
+        assert_eq!(preview.status, LINK_STATUS_ACTIVE);
+        assert!(preview.password_protected);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use tracing::warn;
+use sqlx::{mysql::MySql, MySqlPool, QueryBuilder, Transaction};
+use axum::http::StatusCode;
+
+use crate::handlers::shortlink::TagStats;
+use crate::models::link::LinkView;
+
+
+pub struct Tag;
+
+impl Tag {
+    /// 替换短链的标签，names 为空时清除全部标签；不存在的标签自动创建
+    pub async fn set_link_tags(
+        tx: &mut Transaction<'_, MySql>,
+        link_id: u64,
+        user_id: u64,
+        names: &[String],
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            r#"DELETE FROM link_tags WHERE link_id = ?"#,
+            link_id,
+        )
+        .execute(tx.as_mut())
+        .await
+        .map_err(|e| {
+            warn!("set_link_tags: DB delete error: {} link_id={}", e, link_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB delete error: {}", e))
+        })?;
+
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        // 创建缺失的标签
+        let mut tag_qb: QueryBuilder<MySql> = QueryBuilder::new(
+            "INSERT IGNORE INTO tags (user_id, name) "
+        );
+        tag_qb.push_values(names, |mut b, name| {
+            b.push_bind(user_id).push_bind(name);
+        });
+        tag_qb.build().execute(tx.as_mut())
+            .await
+            .map_err(|e| {
+                warn!("set_link_tags: DB insert error (tags): {} user_id={}", e, user_id);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB insert error: {}", e))
+            })?;
+
+        // 关联短链与标签
+        let mut link_qb: QueryBuilder<MySql> = QueryBuilder::new(
+            "INSERT INTO link_tags (link_id, tag_id) SELECT "
+        );
+        link_qb.push_bind(link_id)
+            .push(", id FROM tags WHERE user_id = ")
+            .push_bind(user_id)
+            .push(" AND name IN (");
+        let mut sep = link_qb.separated(", ");
+        for name in names {
+            sep.push_bind(name);
+        }
+        link_qb.push(")");
+        link_qb.build().execute(tx.as_mut())
+            .await
+            .map_err(|e| {
+                warn!("set_link_tags: DB insert error (link_tags): {} link_id={}", e, link_id);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB insert error: {}", e))
+            })?;
+
+        Ok(())
+    }
+
+    /// 为列表中的短链填充标签
+    pub async fn fill_link_tags(
+        mysql_pool: &MySqlPool,
+        links: &mut [LinkView],
+    ) -> Result<(), (StatusCode, String)> {
+        if links.is_empty() {
+            return Ok(());
+        }
+
+        let mut qb: QueryBuilder<MySql> = QueryBuilder::new(
+            "SELECT lt.link_id, t.name FROM link_tags lt JOIN tags t ON t.id = lt.tag_id WHERE lt.link_id IN ("
+        );
+        let mut sep = qb.separated(", ");
+        for link in links.iter() {
+            sep.push_bind(link.id);
+        }
+        qb.push(") ORDER BY t.name");
+        let rows: Vec<(u64, String)> = qb
+            .build_query_as()
+            .fetch_all(mysql_pool)
+            .await
+            .map_err(|e| {
+                warn!("fill_link_tags: DB select error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+            })?;
+
+        let mut tags: HashMap<u64, Vec<String>> = HashMap::new();
+        for (link_id, name) in rows {
+            tags.entry(link_id).or_default().push(name);
+        }
+        for link in links.iter_mut() {
+            link.tags = tags.remove(&link.id).unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    /// 按标签汇总短链数和点击量（不含回收站中的短链），按标签名排序
+    pub async fn count_by_tag(
+        mysql_pool: &MySqlPool,
+        user_id: u64,
+    ) -> Result<Vec<TagStats>, (StatusCode, String)> {
+        sqlx::query_as!(
+            TagStats,
+            r#"SELECT t.name AS tag,
+                      COUNT(l.id) AS `links!: i64`,
+                      CAST(COALESCE(SUM(l.click_count), 0) AS SIGNED) AS `clicks!: i64`
+               FROM tags t
+               JOIN link_tags lt ON lt.tag_id = t.id
+               JOIN links l ON l.id = lt.link_id AND l.deleted_at IS NULL
+               WHERE t.user_id = ?
+               GROUP BY t.id, t.name
+               ORDER BY t.name"#,
+            user_id,
+        )
+        .fetch_all(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("count_by_tag: DB select error: {} user_id={}", e, user_id);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB select error: {}", e))
+        })
+    }
+}
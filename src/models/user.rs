@@ -12,6 +12,8 @@ pub struct User {
     pub nickname: Option<String>,
     pub password: String,
     pub status: i8,
+    /// 是否允许创建永久（不过期）短链
+    pub allow_permanent: bool,
 }
 
 
@@ -71,7 +73,7 @@ impl User {
             (Some(id), None) => {
                 sqlx::query_as!(
                     User,
-                    "SELECT id, email, nickname, password, status FROM users WHERE id = ? LIMIT 1",
+                    "SELECT id, email, nickname, password, status, allow_permanent AS `allow_permanent: bool` FROM users WHERE id = ? LIMIT 1",
                     id
                 )
                 .fetch_optional(mysql_pool)
@@ -80,7 +82,7 @@ impl User {
             (None, Some(email)) => {
                 sqlx::query_as!(
                     User,
-                    "SELECT id, email, nickname, password, status FROM users WHERE email = ? LIMIT 1",
+                    "SELECT id, email, nickname, password, status, allow_permanent AS `allow_permanent: bool` FROM users WHERE email = ? LIMIT 1",
                     email
                 )
                 .fetch_optional(mysql_pool)
@@ -105,6 +107,27 @@ impl User {
         Ok(row)
     }
 
+    /// 设置账号级过期跳转地址
+    pub async fn set_fallback_url(
+        mysql_pool: &MySqlPool,
+        id: u64,
+        fallback_url: Option<&str>,
+    ) -> Result<(), (StatusCode, String)> {
+        sqlx::query!(
+            "UPDATE users SET fallback_url = ? WHERE id = ?",
+            fallback_url,
+            id,
+        )
+        .execute(mysql_pool)
+        .await
+        .map_err(|e| {
+            warn!("set_fallback_url: DB update error: id={}, err={}", id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB update error: {}", e))
+        })?;
+
+        Ok(())
+    }
+
     /// 读取次数
     async fn read_count(
         redis_mgr: &mut Connection,
@@ -119,7 +142,7 @@ impl User {
     }
 
     /// 检查次数是否超过限制
-    async fn check_limit(
+    pub(crate) async fn check_limit(
         redis_mgr: &mut Connection,
         key: &str,
         limit: i64,
@@ -128,7 +151,8 @@ impl User {
         Ok(cnt >= limit)
     }
 
-    async fn incr_count(
+    /// 次数+1，首次计数时设置过期时间
+    pub(crate) async fn incr_count(
         redis_mgr: &mut Connection,
         key: &str,
         ttl: i64,
@@ -142,7 +166,7 @@ impl User {
         })?;
 
         if count == 1 {
-            // 设置登录失败计数过期时间
+            // 设置计数过期时间
             let _: () = redis_mgr.expire(&key, ttl)
             .await
             .map_err(|e| {
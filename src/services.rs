@@ -2,6 +2,14 @@ pub mod shortlink;
 pub mod tasks;
 pub mod users;
 pub mod background_jobs;
+pub mod short_code;
+pub mod targeting;
+pub mod destination;
+pub mod url_policy;
+pub mod qr;
+pub mod metadata;
+pub mod notifier;
+pub mod transfer;
 
 pub use shortlink::*;
 pub use tasks::*;
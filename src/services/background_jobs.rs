@@ -2,8 +2,8 @@ use std::sync::Arc;
 use tracing::{warn, info};
 use tokio::sync::{mpsc::Receiver, Semaphore};
 use crate::{
-    models::link::Link,
-    services::shortlink::ShortlinkService,
+    models::link::{CachedLink, Link},
+    services::{metadata::fetch_metadata, shortlink::ShortlinkService},
     state::{AppState, ScheduledJobKind},
 };
 
@@ -17,11 +17,13 @@ pub enum BackgroundJob {
         ip: String,
         user_agent: String,
         referer: String,
+        /// A/B 分流命中的目标下标
+        variant: Option<u16>,
     },
     /// 设置点击量和缓存
     SetClickCount {
         short_code: String,
-        long_url: String,
+        cached: CachedLink,
         cache_ttl: i64,
     },
     /// 启动点击量同步
@@ -30,6 +32,19 @@ pub enum BackgroundJob {
     SpawnVisitLogSync,
     /// 启动过期短链删除
     SpawnExpiredLinksDelete,
+    /// 启动到期提醒
+    SpawnExpiryReminders,
+    /// 启动回收站清理
+    SpawnTrashPurge,
+}
+
+
+/// 目标页元数据抓取作业
+/// 抓取要等待外部站点响应，单独排队执行，不占用 Redis 作业的并发和连接
+#[derive(Debug)]
+pub struct MetadataJob {
+    pub short_code: String,
+    pub long_url: String,
 }
 
 
@@ -69,7 +84,8 @@ pub fn spawn_redis_workers(
                             long_url, 
                             ip, 
                             user_agent, 
-                            referer 
+                            referer,
+                            variant,
                         } => {
                             ShortlinkService::push_click_and_log(
                                 &mut conn, 
@@ -77,18 +93,31 @@ pub fn spawn_redis_workers(
                                 long_url, 
                                 ip, 
                                 user_agent, 
-                                referer
+                                referer,
+                                variant,
                             ).await;
                         },
                         BackgroundJob::SetClickCount { // 设置点击量和缓存
                             short_code, 
-                            long_url, 
+                            cached, 
                             cache_ttl 
                         } => {
+                            // 访问次数额度先于缓存写入，避免命中缓存时额度缺失
+                            if let Some(max_clicks) = cached.max_clicks {
+                                if let Err(e) = Link::init_click_quota(
+                                    &mut conn,
+                                    &short_code,
+                                    max_clicks as i64,
+                                    cache_ttl,
+                                ).await {
+                                    warn!("create_shortlink: Redis init_click_quota error: {:?}", e);
+                                }
+                            }
+
                             if let Err(e) = Link::set_shortlink(
                                 &mut conn,
                                 &short_code,
-                                &long_url,
+                                &cached,
                                 cache_ttl,
                             ).await {
                                 warn!("create_shortlink: Redis set_shortlink error: {:?}", e);
@@ -129,17 +158,91 @@ pub fn spawn_redis_workers(
                         },
                         BackgroundJob::SpawnExpiredLinksDelete => { // 启动过期短链删除
                             info!("Syncing expired links start");
+                            let retention_days = state.config.read().await.expired_fallback_retention_days;
                             if let Err(e) = Link::delete_expired_links(
                                 &state.mysql_pool, 
+                                retention_days,
                             ).await {
                                 warn!("Failed to delete expired links: {:?}", e);
                             }
                             state.pending_set.remove(&ScheduledJobKind::DeleteExpired);
                             info!("Synced expired links end");
                         },
+                        BackgroundJob::SpawnExpiryReminders => { // 启动到期提醒
+                            info!("Sending expiry reminders start");
+                            match ShortlinkService::send_expiry_reminders(
+                                &state,
+                                100
+                            ).await {
+                                Ok(sent) => info!("Sent {} expiry reminders", sent),
+                                Err(e) => warn!("Failed to send expiry reminders: {:?}", e),
+                            }
+                            state.pending_set.remove(&ScheduledJobKind::RemindExpiring);
+                            info!("Sending expiry reminders end");
+                        },
+                        BackgroundJob::SpawnTrashPurge => { // 启动回收站清理
+                            info!("Purging trash start");
+                            let grace_secs = state.config.read().await.trash_retention_secs;
+                            if let Err(e) = Link::purge_deleted_links(
+                                &state.mysql_pool,
+                                &mut conn,
+                                grace_secs,
+                                100
+                            ).await {
+                                warn!("Failed to purge trash: {:?}", e);
+                            }
+                            state.pending_set.remove(&ScheduledJobKind::PurgeTrash);
+                            info!("Purged trash end");
+                        },
                     };
                 });
             }
         }
     });
-}
\ No newline at end of file
+}
+
+
+/// 启动目标页元数据抓取的“固定并发 N + 有界队列”，作业不取 Redis 连接
+pub fn spawn_metadata_workers(
+    state: Arc<AppState>,
+    mut rx: Receiver<MetadataJob>,
+    max_concurrency: usize,
+) {
+    let sem = Arc::new(Semaphore::new(max_concurrency));
+
+    tokio::spawn(async move {
+        while let Some(MetadataJob { short_code, long_url }) = rx.recv().await {
+            let state = state.clone();
+            let permit = sem
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("semaphore closed");
+
+            tokio::spawn(async move {
+                let _permit = permit;
+                // 黑名单可能已更新，抓取前重新校验；跳转目标由抓取客户端逐跳校验
+                if let Err(msg) = state.url_policy.check(&long_url) {
+                    warn!("fetch_link_metadata: 目标地址不允许抓取: short_code={}, err={}", short_code, msg);
+                    return;
+                }
+                let max_bytes = state.config.read().await.link_metadata_max_bytes;
+                match fetch_metadata(&state.metadata_client, &long_url, max_bytes).await {
+                    Ok(meta) => {
+                        if let Err(e) = Link::update_metadata(
+                            &state.mysql_pool,
+                            &short_code,
+                            &long_url,
+                            meta.title.as_deref(),
+                            meta.description.as_deref(),
+                            meta.favicon.as_deref(),
+                        ).await {
+                            warn!("fetch_link_metadata: update_metadata error: {:?}", e);
+                        }
+                    },
+                    Err(e) => warn!("fetch_link_metadata: 抓取失败: short_code={}, err={}", short_code, e),
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,119 @@
+use url::{Url, form_urlencoded};
+
+use crate::handlers::shortlink::UtmTemplate;
+
+
+/// 拼接最终跳转地址
+/// 参数按 长链自带 → UTM 模板 → 透传的访问参数 依次合并，同名参数以后者为准
+pub fn build_destination(
+    long_url: &str,
+    incoming_query: Option<&str>,
+    utm: Option<&UtmTemplate>,
+) -> String {
+    let mut extra: Vec<(String, String)> = Vec::new();
+    if let Some(utm) = utm {
+        extra.extend(utm.pairs().map(|(k, v)| (k.to_string(), v.to_string())));
+    }
+    if let Some(query) = incoming_query {
+        merge_pairs(
+            &mut extra,
+            form_urlencoded::parse(query.as_bytes()).into_owned(),
+        );
+    }
+    if extra.is_empty() {
+        return long_url.to_string();
+    }
+
+    let Ok(mut url) = Url::parse(long_url) else {
+        return long_url.to_string();
+    };
+    // 长链自带的参数按原文保留，不重新编码；只去掉被覆盖的同名参数
+    let kept = url
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|segment| {
+            !segment.is_empty()
+                && !form_urlencoded::parse(segment.as_bytes())
+                    .next()
+                    .is_some_and(|(key, _)| extra.iter().any(|(k, _)| *k == key))
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    let query = form_urlencoded::Serializer::for_suffix(kept, 0)
+        .extend_pairs(&extra)
+        .finish();
+    url.set_query(Some(&query));
+
+    url.into()
+}
+
+/// 合并参数：移除同名参数后追加到末尾
+fn merge_pairs(
+    pairs: &mut Vec<(String, String)>,
+    extra: impl IntoIterator<Item = (String, String)>,
+) {
+    for (key, value) in extra {
+        pairs.retain(|(k, _)| *k != key);
+        pairs.push((key, value));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utm() -> UtmTemplate {
+        UtmTemplate {
+            source: Some("newsletter".into()),
+            medium: Some("email".into()),
+            campaign: Some("spring sale".into()),
+        }
+    }
+
+    #[test]
+    fn test_unchanged_without_params() {
+        let url = "https://www.example.com/a?b=1";
+        assert_eq!(build_destination(url, None, None), url);
+        assert_eq!(build_destination(url, Some(""), None), url);
+    }
+
+    #[test]
+    fn test_forward_query_merges() {
+        assert_eq!(
+            build_destination("https://www.example.com/a?b=1&c=2", Some("c=3&d=x%20y"), None),
+            "https://www.example.com/a?b=1&c=3&d=x+y",
+        );
+        assert_eq!(
+            build_destination("https://www.example.com/a#top", Some("q=%E4%BD%A0"), None),
+            "https://www.example.com/a?q=%E4%BD%A0#top",
+        );
+    }
+
+    #[test]
+    fn test_original_query_preserved() {
+        // 长链原有参数按原文保留：不把 %20 改成 +，不给无值参数补 =，不重新转义
+        assert_eq!(
+            build_destination("https://www.example.com/a?sig=a%2Fb&flag&q=x%20y", Some("ref=tw"), None),
+            "https://www.example.com/a?sig=a%2Fb&flag&q=x%20y&ref=tw",
+        );
+        assert_eq!(
+            build_destination("https://www.example.com/a?sig=a%2Fb&flag", Some("flag=1"), None),
+            "https://www.example.com/a?sig=a%2Fb&flag=1",
+        );
+    }
+
+    #[test]
+    fn test_utm_template() {
+        assert_eq!(
+            build_destination("https://www.example.com/a?utm_source=old", None, Some(&utm())),
+            "https://www.example.com/a?utm_source=newsletter&utm_medium=email&utm_campaign=spring+sale",
+        );
+        // 透传参数优先于模板
+        assert_eq!(
+            build_destination("https://www.example.com/a", Some("utm_source=x"), Some(&utm())),
+            "https://www.example.com/a?utm_medium=email&utm_campaign=spring+sale&utm_source=x",
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use reqwest::{
+    Client,
+    ClientBuilder,
+    dns::{Addrs, Name, Resolve, Resolving},
+    header,
+    redirect::Policy,
+};
+use url::Url;
+
+use crate::services::url_policy::{is_internal_ip, UrlPolicy};
+
+
+/// 标题最大长度（字符），与 meta_title 列一致
+const MAX_TITLE_CHARS: usize = 255;
+/// 描述最大长度（字符），与 meta_description 列一致
+const MAX_DESCRIPTION_CHARS: usize = 1000;
+/// 最多跟随的跳转次数
+const MAX_REDIRECTS: usize = 3;
+
+
+/// 目标页面元数据
+#[derive(Debug, Default, PartialEq)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub favicon: Option<String>,
+}
+
+
+/// 构建抓取用的 HTTP 客户端：连接与整体请求共用超时，最多跟随 3 次跳转
+/// 每次跳转的目标都重新经过 url_policy 校验，域名只连接解析到的公网地址，防止借跳转或 DNS 访问内网
+/// 不走系统代理，否则域名由代理解析，绕过地址过滤
+pub fn build_client(timeout_ms: u64, url_policy: Arc<UrlPolicy>) -> Result<Client, String> {
+    client_builder(timeout_ms)
+        .redirect(Policy::custom(move |attempt| {
+            if attempt.previous().len() > MAX_REDIRECTS {
+                return attempt.error("too many redirects");
+            }
+            match url_policy.check(attempt.url().as_str()) {
+                Ok(()) => attempt.follow(),
+                Err(msg) => attempt.error(format!("redirect not allowed: {}", msg)),
+            }
+        }))
+        .dns_resolver(Arc::new(PublicResolver))
+        .no_proxy()
+        .build()
+        .map_err(|e| format!("build metadata client error: {}", e))
+}
+
+/// 超时与 User-Agent 等公共设置
+fn client_builder(timeout_ms: u64) -> ClientBuilder {
+    Client::builder()
+        .timeout(Duration::from_millis(timeout_ms))
+        .connect_timeout(Duration::from_millis(timeout_ms))
+        .user_agent(concat!("tokio-shortlink/", env!("CARGO_PKG_VERSION")))
+}
+
+
+/// 只返回公网地址的 DNS 解析器，解析结果全部为内网地址时报错
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| !is_internal_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} does not resolve to a public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// 抓取目标页面并解析元数据，最多读取 max_bytes 字节
+pub async fn fetch_metadata(
+    client: &Client,
+    url: &str,
+    max_bytes: usize,
+) -> Result<PageMetadata, String> {
+    let mut res = client
+        .get(url)
+        .header(header::ACCEPT, "text/html")
+        .send()
+        .await
+        .map_err(|e| format!("request error: {}", e))?;
+    if !res.status().is_success() {
+        return Err(format!("unexpected status: {}", res.status()));
+    }
+    let is_html = res.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_none_or(|v| v.to_ascii_lowercase().contains("html"));
+    if !is_html {
+        return Err("not an html page".into());
+    }
+
+    // 相对地址以跳转后的最终地址为准
+    let base = res.url().clone();
+    let mut body = Vec::new();
+    while let Some(chunk) = res.chunk().await.map_err(|e| format!("read body error: {}", e))? {
+        let remaining = max_bytes - body.len();
+        if chunk.len() >= remaining {
+            body.extend_from_slice(&chunk[..remaining]);
+            break;
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(parse_metadata(&String::from_utf8_lossy(&body), &base))
+}
+
+/// 从 HTML 中提取 <title>、描述与图标，只扫描到 </head> 或 <body> 为止
+/// 描述优先取 name="description"，其次 og:description；未声明图标时使用 /favicon.ico
+pub fn parse_metadata(html: &str, base: &Url) -> PageMetadata {
+    let mut meta = PageMetadata::default();
+    let mut og_description = None;
+    // 仅转换 ASCII，字节下标与原文一致
+    let lower = html.to_ascii_lowercase();
+    let mut pos = 0;
+
+    while let Some(start) = lower[pos..].find('<').map(|i| pos + i) {
+        let Some(end) = lower[start..].find('>').map(|i| start + i) else {
+            break;
+        };
+        let tag = &html[start + 1..end];
+        pos = end + 1;
+
+        let name = tag
+            .split(|c: char| c.is_ascii_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "title" if meta.title.is_none() => {
+                if let Some(close) = lower[pos..].find("</title") {
+                    meta.title = clean_text(&decode_entities(&html[pos..pos + close]), MAX_TITLE_CHARS);
+                    pos += close;
+                }
+            },
+            "meta" => {
+                let attrs = parse_attrs(tag);
+                let key = attr(&attrs, "name")
+                    .or_else(|| attr(&attrs, "property"))
+                    .map(|k| k.to_ascii_lowercase());
+                let content = attr(&attrs, "content")
+                    .and_then(|c| clean_text(c, MAX_DESCRIPTION_CHARS));
+                match key.as_deref() {
+                    Some("description") if meta.description.is_none() => meta.description = content,
+                    Some("og:description") if og_description.is_none() => og_description = content,
+                    _ => {},
+                }
+            },
+            "link" if meta.favicon.is_none() => {
+                let attrs = parse_attrs(tag);
+                let is_icon = attr(&attrs, "rel").is_some_and(|rel| {
+                    rel.split_ascii_whitespace().any(|r| r.eq_ignore_ascii_case("icon"))
+                });
+                if is_icon {
+                    meta.favicon = attr(&attrs, "href").and_then(|href| resolve_http(base, href));
+                }
+            },
+            // 脚本和样式内容可能包含 '<'，直接跳过
+            "script" | "style" => {
+                let close = format!("</{}", name);
+                match lower[pos..].find(&close) {
+                    Some(i) => pos += i,
+                    None => break,
+                }
+            },
+            "/head" | "body" => break,
+            _ => {},
+        }
+    }
+
+    meta.description = meta.description.or(og_description);
+    if meta.favicon.is_none() {
+        meta.favicon = resolve_http(base, "/favicon.ico");
+    }
+    meta
+}
+
+/// 解析相对地址，只接受 http / https
+fn resolve_http(base: &Url, href: &str) -> Option<String> {
+    base.join(href.trim())
+        .ok()
+        .filter(|u| matches!(u.scheme(), "http" | "https"))
+        .map(String::from)
+}
+
+/// 解析标签属性，属性名转为小写，值解码 HTML 实体
+fn parse_attrs(tag: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    // 跳过标签名
+    let mut rest = tag.trim_start_matches(|c: char| !c.is_ascii_whitespace());
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if rest.is_empty() {
+            break;
+        }
+        let name_end = rest
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let end = inner.find(quote).unwrap_or(inner.len());
+                    (&inner[..end], inner.get(end + 1..).unwrap_or_default())
+                },
+                _ => {
+                    let end = after.find(|c: char| c.is_ascii_whitespace()).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                },
+            };
+            value = decode_entities(raw);
+            rest = remaining;
+        }
+        if !name.is_empty() {
+            attrs.push((name, value));
+        }
+    }
+    attrs
+}
+
+/// 取属性值
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+/// 合并空白并截断，内容为空时返回 None
+fn clean_text(raw: &str, max_chars: usize) -> Option<String> {
+    let text = raw
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.is_empty() {
+        return None;
+    }
+    Some(text.chars().take(max_chars).collect())
+}
+
+/// 解码常见 HTML 实体，无法识别的原样保留
+fn decode_entities(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(i) = rest.find('&') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let decoded = rest
+            .find(';')
+            .filter(|&end| end <= 10)
+            .and_then(|end| decode_entity(&rest[1..end]).map(|c| (c, end)));
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            },
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => entity.strip_prefix('#')?.parse().ok()?,
+            };
+            char::from_u32(code)
+        },
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// 本地 HTTP 替身：接受一次连接，delay 后返回给定响应体
+    async fn serve_once(content_type: &'static str, body: String, delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            tokio::time::sleep(delay).await;
+            let res = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                content_type,
+                body.len(),
+                body,
+            );
+            let _ = socket.write_all(res.as_bytes()).await;
+        });
+        format!("http://{}/docs/page.html", addr)
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        let base = Url::parse("https://example.com/docs/page.html").unwrap();
+        let html = r#"<!doctype html>
+            <html><head>
+              <meta charset="utf-8">
+              <TITLE>
+                Tom &amp; Jerry &#8211; Home
+              </TITLE>
+              <script>if (a < b) { document.title = "x"; }</script>
+              <meta property="og:description" content="From OG">
+              <meta name=description content='Cats &quot;and&quot; mice'>
+              <link rel="shortcut icon" href="../img/fav.png" />
+            </head>
+            <body><title>ignored</title></body></html>"#;
+        assert_eq!(parse_metadata(html, &base), PageMetadata {
+            title: Some("Tom & Jerry \u{2013} Home".into()),
+            description: Some("Cats \"and\" mice".into()),
+            favicon: Some("https://example.com/img/fav.png".into()),
+        });
+
+        // 缺省值：og:description 兜底，/favicon.ico
+        let html = r#"<head><meta property="og:description" content="From OG"></head>"#;
+        assert_eq!(parse_metadata(html, &base), PageMetadata {
+            title: None,
+            description: Some("From OG".into()),
+            favicon: Some("https://example.com/favicon.ico".into()),
+        });
+
+        // 超长标题被截断
+        let html = format!("<title>{}</title>", "a".repeat(300));
+        assert_eq!(parse_metadata(&html, &base).title.unwrap().len(), MAX_TITLE_CHARS);
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#x41;&#66; &unknown; & c"), "a <b> AB &unknown; & c");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_metadata() {
+        // 替身监听在回环地址，测试客户端不做地址过滤
+        let client = client_builder(1000).build().unwrap();
+
+        let url = serve_once(
+            "text/html; charset=utf-8",
+            "<html><head><title>Stand-in</title><link rel=icon href=/i.png></head></html>".into(),
+            Duration::ZERO,
+        ).await;
+        let meta = fetch_metadata(&client, &url, 64 * 1024).await.unwrap();
+        assert_eq!(meta.title.as_deref(), Some("Stand-in"));
+        assert_eq!(meta.favicon, Some(url.replace("/docs/page.html", "/i.png")));
+
+        // 超出大小限制的部分不解析
+        let body = format!("<html><head>{}<title>Too far</title></head></html>", " ".repeat(4096));
+        let url = serve_once("text/html", body, Duration::ZERO).await;
+        let meta = fetch_metadata(&client, &url, 1024).await.unwrap();
+        assert_eq!(meta.title, None);
+
+        // 非 HTML
+        let url = serve_once("application/json", "{}".into(), Duration::ZERO).await;
+        assert!(fetch_metadata(&client, &url, 1024).await.is_err());
+
+        // 超时
+        let client = client_builder(200).build().unwrap();
+        let url = serve_once("text/html", "<title>Slow</title>".into(), Duration::from_secs(2)).await;
+        assert!(fetch_metadata(&client, &url, 1024).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_public_resolver() {
+        // 解析到回环地址的域名不允许连接
+        let name: Name = "localhost".parse().unwrap();
+        assert!(PublicResolver.resolve(name).await.is_err());
+    }
+}
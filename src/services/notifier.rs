@@ -0,0 +1,169 @@
+use std::{future::Future, pin::Pin, time::Duration};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt};
+use tracing::info;
+
+use crate::config::AppConfig;
+
+
+/// 短链即将过期的提醒事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ExpiryReminder {
+    /// 事件类型，固定为 link.expiring
+    pub event: &'static str,
+    pub user_id: u64,
+    pub email: String,
+    pub link_id: u64,
+    pub short_code: String,
+    pub short_url: String,
+    pub long_url: String,
+    pub expire_at: DateTime<Utc>,
+}
+
+impl ExpiryReminder {
+    pub const EVENT: &'static str = "link.expiring";
+}
+
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// 提醒发送方式
+pub trait Notifier: Send + Sync {
+    /// 发送成功才会标记为已提醒，失败的提醒在下一轮重试
+    fn notify<'a>(&'a self, reminder: &'a ExpiryReminder) -> NotifyFuture<'a>;
+}
+
+
+/// 写入服务日志
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn notify<'a>(&'a self, reminder: &'a ExpiryReminder) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            info!(
+                "expiry_reminder: user_id={}, email={}, short_code={}, expire_at={}",
+                reminder.user_id,
+                reminder.email,
+                reminder.short_code,
+                reminder.expire_at.to_rfc3339(),
+            );
+            Ok(())
+        })
+    }
+}
+
+
+/// 追加写入文件，每行一条 JSON
+pub struct FileNotifier {
+    path: String,
+}
+
+impl Notifier for FileNotifier {
+    fn notify<'a>(&'a self, reminder: &'a ExpiryReminder) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let mut line = serde_json::to_string(reminder)
+                .map_err(|e| format!("serialize error: {}", e))?;
+            line.push('\n');
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await
+                .map_err(|e| format!("open {} error: {}", self.path, e))?;
+            file.write_all(line.as_bytes())
+                .await
+                .map_err(|e| format!("write {} error: {}", self.path, e))
+        })
+    }
+}
+
+
+/// POST JSON 到 Webhook，非 2xx 视为失败
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, reminder: &'a ExpiryReminder) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let res = self.client
+                .post(&self.url)
+                .json(reminder)
+                .send()
+                .await
+                .map_err(|e| format!("webhook request error: {}", e))?;
+            if !res.status().is_success() {
+                return Err(format!("webhook returned {}", res.status()));
+            }
+            Ok(())
+        })
+    }
+}
+
+
+/// 根据配置构建提醒发送方式：log（默认）/ file / webhook
+pub fn build_notifier(cfg: &AppConfig) -> Result<Box<dyn Notifier>, String> {
+    match cfg.expiry_notifier.as_str() {
+        "log" => Ok(Box::new(LogNotifier)),
+        "file" => {
+            let path = cfg.expiry_notifier_file
+                .clone()
+                .filter(|p| !p.is_empty())
+                .ok_or("EXPIRY_NOTIFIER_FILE is required for the file notifier")?;
+            Ok(Box::new(FileNotifier { path }))
+        },
+        "webhook" => {
+            let url = cfg.expiry_notifier_webhook_url
+                .clone()
+                .filter(|u| !u.is_empty())
+                .ok_or("EXPIRY_NOTIFIER_WEBHOOK_URL is required for the webhook notifier")?;
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_millis(cfg.expiry_notifier_timeout_ms))
+                .build()
+                .map_err(|e| format!("build webhook client error: {}", e))?;
+            Ok(Box::new(WebhookNotifier { client, url }))
+        },
+        other => Err(format!("Unknown EXPIRY_NOTIFIER: {}", other)),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reminder(short_code: &str) -> ExpiryReminder {
+        ExpiryReminder {
+            event: ExpiryReminder::EVENT,
+            user_id: 1,
+            email: "test1@example.com".into(),
+            link_id: 7,
+            short_code: short_code.into(),
+            short_url: format!("http://127.0.0.1:3000/s/{}", short_code),
+            long_url: "https://www.example.com".into(),
+            expire_at: DateTime::parse_from_rfc3339("2026-10-16T08:00:00Z").unwrap().to_utc(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_notifier() {
+        let path = std::env::temp_dir().join(format!("expiry_reminders_{}.jsonl", uuid::Uuid::new_v4()));
+        let notifier = FileNotifier { path: path.to_string_lossy().into_owned() };
+        notifier.notify(&reminder("abc")).await.unwrap();
+        notifier.notify(&reminder("def")).await.unwrap();
+
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "link.expiring");
+        assert_eq!(lines[0]["short_code"], "abc");
+        assert_eq!(lines[1]["expire_at"], "2026-10-16T08:00:00Z");
+
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt::Write as _;
+use qrcode::{Color, QrCode};
+
+
+/// 二维码模块矩阵，包含四周留白
+struct QrMatrix {
+    /// 每边模块数（含留白）
+    width: usize,
+    /// 按行排列，true 为深色
+    dark: Vec<bool>,
+}
+
+impl QrMatrix {
+    fn new(data: &str, margin: u32) -> Result<Self, String> {
+        let code = QrCode::new(data.as_bytes())
+            .map_err(|e| format!("QR encode error: {}", e))?;
+        let inner = code.width();
+        let margin = margin as usize;
+        let width = inner + margin * 2;
+        let colors = code.to_colors();
+
+        let mut dark = vec![false; width * width];
+        for y in 0..inner {
+            for x in 0..inner {
+                dark[(y + margin) * width + x + margin] = colors[y * inner + x] == Color::Dark;
+            }
+        }
+
+        Ok(Self { width, dark })
+    }
+
+    fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.dark[y * self.width + x]
+    }
+}
+
+
+/// 渲染 SVG 二维码，size 为图片边长（像素），margin 为留白模块数
+pub fn render_svg(data: &str, size: u32, margin: u32) -> Result<String, String> {
+    let matrix = QrMatrix::new(data, margin)?;
+    let w = matrix.width;
+
+    let mut path = String::new();
+    for y in 0..w {
+        for x in 0..w {
+            if matrix.is_dark(x, y) {
+                let _ = write!(path, "M{x},{y}h1v1h-1z");
+            }
+        }
+    }
+
+    Ok(format!(
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<svg xmlns="http://www.w3.org/2000/svg" width="{size}" height="{size}" viewBox="0 0 {w} {w}" shape-rendering="crispEdges"><rect width="{w}" height="{w}" fill="#fff"/><path fill="#000" d="{path}"/></svg>
+"##
+    ))
+}
+
+
+/// 渲染 PNG 二维码（8 位灰度）
+/// 每个模块取整数像素，实际边长为不超过 size 的最大整数倍，至少每模块 1 像素
+pub fn render_png(data: &str, size: u32, margin: u32) -> Result<Vec<u8>, String> {
+    let matrix = QrMatrix::new(data, margin)?;
+    let scale = (size as usize / matrix.width).max(1);
+    let side = matrix.width * scale;
+
+    let mut pixels = vec![0xffu8; side * side];
+    for y in 0..side {
+        for x in 0..side {
+            if matrix.is_dark(x / scale, y / scale) {
+                pixels[y * side + x] = 0;
+            }
+        }
+    }
+
+    let mut buf = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buf, side as u32, side as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("PNG encode error: {}", e))?;
+    writer
+        .write_image_data(&pixels)
+        .map_err(|e| format!("PNG encode error: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("PNG encode error: {}", e))?;
+
+    Ok(buf)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://sho.rt/s/abc123";
+
+    #[test]
+    fn test_matrix_margin() {
+        let plain = QrMatrix::new(URL, 0).unwrap();
+        let padded = QrMatrix::new(URL, 4).unwrap();
+        assert_eq!(padded.width, plain.width + 8);
+        // 留白全部为浅色，定位图案左上角为深色
+        assert!((0..padded.width).all(|x| !padded.is_dark(x, 0)));
+        assert!(padded.is_dark(4, 4));
+        assert!(plain.is_dark(0, 0));
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let svg = render_svg(URL, 300, 4).unwrap();
+        assert!(svg.contains(r#"width="300""#));
+        assert!(svg.contains("<path"));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let png = render_png(URL, 300, 4).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+
+        let side = QrMatrix::new(URL, 4).unwrap().width;
+        let decoder = png::Decoder::new(png.as_slice());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!(info.width as usize % side, 0);
+        assert!(info.width <= 300);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::HashSet, fs};
+use sha2::{Digest, Sha256};
+use password_hash::rand_core::{OsRng, RngCore};
+use crate::config::AppConfig;
+
+
+/// short_code 列长度（VARCHAR(16)）
+pub const SHORT_CODE_MAX_LEN: usize = 16;
+/// 字母表最小长度，保证 u64 范围内的 id 编码后不超过列长度
+const MIN_ALPHABET_LEN: usize = 16;
+/// Feistel 轮数
+const FEISTEL_ROUNDS: u8 = 4;
+
+
+/// 短码生成策略
+pub trait ShortCodeGenerator: Send + Sync {
+    /// id 为新插入行的自增 id，attempt 为碰撞后的重试次数（从 0 开始）
+    fn generate(&self, id: u64, attempt: u32) -> String;
+}
+
+
+/// 按字母表进制编码，左侧用字母表首字符补齐到 min_len
+fn encode(mut n: u128, alphabet: &[u8], min_len: usize) -> String {
+    let base = alphabet.len() as u128;
+    let mut buf = Vec::new();
+    while n > 0 {
+        buf.push(alphabet[(n % base) as usize]);
+        n /= base;
+    }
+    while buf.len() < min_len.max(1) {
+        buf.push(alphabet[0]);
+    }
+    buf.reverse();
+    // 字母表已在构建时校验为 ASCII
+    String::from_utf8(buf).unwrap()
+}
+
+
+/// 顺序生成：自增 id 直接编码（可被遍历）
+pub struct SequentialGenerator {
+    alphabet: Vec<u8>,
+    min_length: usize,
+}
+
+impl ShortCodeGenerator for SequentialGenerator {
+    fn generate(&self, id: u64, attempt: u32) -> String {
+        encode(id as u128 + attempt as u128, &self.alphabet, self.min_length)
+    }
+}
+
+
+/// 置换生成：对 id 做带密钥的 Feistel 置换后再编码
+/// 同一长度内是双射，不同长度的短码互不相同，因此不会与其它 id 的短码冲突
+pub struct FeistelGenerator {
+    alphabet: Vec<u8>,
+    min_length: usize,
+    key: [u8; 32],
+}
+
+impl FeistelGenerator {
+    /// 轮函数：SHA-256(key || round || attempt || right) 取前 16 字节
+    fn round(&self, round: u8, attempt: u32, right: u128) -> u128 {
+        let mut hasher = Sha256::new();
+        hasher.update(self.key);
+        hasher.update([round]);
+        hasher.update(attempt.to_le_bytes());
+        hasher.update(right.to_le_bytes());
+        let digest = hasher.finalize();
+        let mut buf = [0u8; 16];
+        buf.copy_from_slice(&digest[..16]);
+        u128::from_le_bytes(buf)
+    }
+
+    /// 在 [0, 2^(2 * half_bits)) 上的置换
+    fn permute(&self, x: u128, half_bits: u32, attempt: u32) -> u128 {
+        let mask = (1u128 << half_bits) - 1;
+        let mut left = x >> half_bits;
+        let mut right = x & mask;
+        for round in 0..FEISTEL_ROUNDS {
+            let f = self.round(round, attempt, right) & mask;
+            (left, right) = (right, left ^ f);
+        }
+        (left << half_bits) | right
+    }
+}
+
+impl ShortCodeGenerator for FeistelGenerator {
+    fn generate(&self, id: u64, attempt: u32) -> String {
+        let base = self.alphabet.len() as u128;
+        let id = id as u128;
+
+        // 找到能容纳 id 的最短长度（不少于 min_length）
+        let mut len = self.min_length.max(1);
+        let mut domain = base.pow(len as u32);
+        while domain <= id {
+            len += 1;
+            domain *= base;
+        }
+
+        // 最小的偶数位宽 2h 使 2^(2h) >= domain，超出 domain 的结果继续置换（cycle walking）
+        let bits = 128 - (domain - 1).leading_zeros();
+        let half_bits = bits.div_ceil(2).max(1);
+        let mut x = id;
+        loop {
+            x = self.permute(x, half_bits, attempt);
+            if x < domain {
+                break;
+            }
+        }
+
+        encode(x, &self.alphabet, len)
+    }
+}
+
+
+/// 随机生成：使用系统安全随机数，碰撞时由调用方重试
+pub struct RandomGenerator {
+    alphabet: Vec<u8>,
+    length: usize,
+}
+
+impl ShortCodeGenerator for RandomGenerator {
+    fn generate(&self, _id: u64, _attempt: u32) -> String {
+        let n = self.alphabet.len() as u32;
+        // 拒绝采样，避免取模偏差
+        let zone = u32::MAX - (u32::MAX % n);
+        let mut buf = Vec::with_capacity(self.length);
+        while buf.len() < self.length {
+            let v = OsRng.next_u32();
+            if v < zone {
+                buf.push(self.alphabet[(v % n) as usize]);
+            }
+        }
+        String::from_utf8(buf).unwrap()
+    }
+}
+
+
+/// 根据配置构建短码生成器
+pub fn build_generator(cfg: &AppConfig) -> Result<Box<dyn ShortCodeGenerator>, String> {
+    let alphabet = cfg.short_code_alphabet.as_bytes().to_vec();
+    if alphabet.len() < MIN_ALPHABET_LEN {
+        return Err(format!("SHORT_CODE_ALPHABET must have at least {} characters", MIN_ALPHABET_LEN));
+    }
+    if !alphabet.iter().all(|c| c.is_ascii_alphanumeric() || *c == b'-' || *c == b'_') {
+        return Err("SHORT_CODE_ALPHABET may only contain [A-Za-z0-9_-]".into());
+    }
+    let mut sorted = alphabet.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    if sorted.len() != alphabet.len() {
+        return Err("SHORT_CODE_ALPHABET must not contain duplicate characters".into());
+    }
+
+    let min_length = cfg.short_code_min_length;
+    if min_length == 0 || min_length > SHORT_CODE_MAX_LEN {
+        return Err(format!("SHORT_CODE_MIN_LENGTH must be between 1 and {}", SHORT_CODE_MAX_LEN));
+    }
+
+    match cfg.short_code_strategy.as_str() {
+        "sequential" => Ok(Box::new(SequentialGenerator { alphabet, min_length })),
+        "feistel" => {
+            if cfg.short_code_secret.is_empty() {
+                return Err("SHORT_CODE_SECRET is required for the feistel strategy".into());
+            }
+            let key: [u8; 32] = Sha256::digest(cfg.short_code_secret.as_bytes()).into();
+            Ok(Box::new(FeistelGenerator { alphabet, min_length, key }))
+        },
+        "random" => Ok(Box::new(RandomGenerator { alphabet, length: min_length })),
+        other => Err(format!("Unknown SHORT_CODE_STRATEGY: {}", other)),
+    }
+}
+
+
+/// 自定义短码校验规则
+pub struct CustomCodePolicy {
+    min_length: usize,
+    max_length: usize,
+    /// 保留字（小写，整词匹配）
+    reserved: HashSet<String>,
+    /// 敏感词（小写，子串匹配）
+    profanity: Vec<String>,
+}
+
+impl CustomCodePolicy {
+    /// 根据配置构建，敏感词文件每行一个词，忽略空行和 # 注释
+    pub fn from_config(cfg: &AppConfig) -> Result<Self, String> {
+        let min_length = cfg.custom_code_min_length;
+        let max_length = cfg.custom_code_max_length;
+        if min_length == 0 || min_length > max_length || max_length > SHORT_CODE_MAX_LEN {
+            return Err(format!(
+                "CUSTOM_CODE_MIN_LENGTH/CUSTOM_CODE_MAX_LENGTH must satisfy 1 <= min <= max <= {}",
+                SHORT_CODE_MAX_LEN
+            ));
+        }
+
+        let reserved = cfg.custom_code_reserved_words
+            .split(',')
+            .map(|w| w.trim().to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        let profanity = match cfg.custom_code_profanity_file.as_deref().filter(|p| !p.is_empty()) {
+            Some(path) => fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read CUSTOM_CODE_PROFANITY_FILE {}: {}", path, e))?
+                .lines()
+                .map(|w| w.trim().to_lowercase())
+                .filter(|w| !w.is_empty() && !w.starts_with('#'))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(Self { min_length, max_length, reserved, profanity })
+    }
+
+    /// 校验自定义短码，失败时返回可直接展示给客户端的原因
+    pub fn check(&self, code: &str) -> Result<(), String> {
+        if code.len() < self.min_length || code.len() > self.max_length {
+            return Err(format!(
+                "Short code must be between {} and {} characters",
+                self.min_length, self.max_length
+            ));
+        }
+
+        if !code.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_') {
+            return Err("Short code may only contain letters, digits, '-' and '_'".into());
+        }
+
+        let lower = code.to_ascii_lowercase();
+        if self.reserved.contains(&lower) {
+            return Err("Short code is reserved".into());
+        }
+
+        if self.profanity.iter().any(|w| lower.contains(w.as_str())) {
+            return Err("Short code contains a blocked word".into());
+        }
+
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const BASE62: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+    #[test]
+    fn test_encode_base62() {
+        assert_eq!(encode(1, BASE62, 1), "1");
+        assert_eq!(encode(62, BASE62, 1), "10");
+        assert_eq!(encode(62 * 62, BASE62, 1), "100");
+        assert_eq!(encode(1, BASE62, 4), "0001");
+    }
+
+    #[test]
+    fn test_feistel_is_injective_and_padded() {
+        let generator = FeistelGenerator {
+            alphabet: BASE62.to_vec(),
+            min_length: 2,
+            key: Sha256::digest(b"secret").into(),
+        };
+        let mut seen = HashSet::new();
+        // 覆盖长度 2 的整个值域以及跨越到长度 3 的部分
+        for id in 0..(62 * 62 + 500) {
+            let code = generator.generate(id, 0);
+            assert!(code.len() >= 2);
+            assert!(seen.insert(code), "duplicate code for id {}", id);
+        }
+        // 连续 id 不应得到连续短码
+        assert_ne!(generator.generate(1, 0), encode(1, BASE62, 2));
+    }
+
+    #[test]
+    fn test_custom_code_policy() {
+        let policy = CustomCodePolicy {
+            min_length: 3,
+            max_length: 16,
+            reserved: ["admin".to_string(), "login".to_string()].into_iter().collect(),
+            profanity: vec!["darn".to_string()],
+        };
+        assert!(policy.check("my-code_1").is_ok());
+        assert!(policy.check("ab").is_err());
+        assert!(policy.check(&"a".repeat(17)).is_err());
+        assert!(policy.check("a/b").is_err());
+        assert!(policy.check("has space").is_err());
+        assert!(policy.check("emoji😀").is_err());
+        assert!(policy.check("Admin").is_err());
+        assert!(policy.check("adminx").is_ok());
+        assert!(policy.check("xDarnx").is_err());
+    }
+
+    #[test]
+    fn test_random_length_and_charset() {
+        let generator = RandomGenerator { alphabet: BASE62.to_vec(), length: 8 };
+        let code = generator.generate(1, 0);
+        assert_eq!(code.len(), 8);
+        assert!(code.bytes().all(|c| BASE62.contains(&c)));
+    }
+}
@@ -1,43 +1,418 @@
-use tracing::warn;
-use axum::http::StatusCode;
+use std::{io, sync::Arc};
+use tracing::{info, warn};
+use axum::{body::Bytes, http::StatusCode};
+use futures::{stream, Stream, StreamExt};
+use argon2::{Argon2, PasswordHash, PasswordVerifier};
+use password_hash::{PasswordHasher, SaltString, rand_core::OsRng};
 use deadpool_redis::Connection;
+use sha2::{Digest, Sha256};
+use sqlx::{Acquire, MySql, Transaction};
+use chrono::{DateTime, SubsecRound, Utc};
+use url::Url;
+use uuid::Uuid;
+use validator::Validate;
 use crate::{
-    handlers::shortlink::LinkQuery, 
-    models::link::{Link, LinkView}, 
+    handlers::pages::ErrorPageKind,
+    handlers::shortlink::{
+        BatchErrorCode,
+        BatchItemResult,
+        ExportFormat,
+        ImportReport,
+        ImportStatus,
+        LinkList,
+        LinkQuery,
+        LinkTargets,
+        LinkUpdateReq,
+        LinkVariant,
+        RedirectType,
+        ShortlinkCreateReq,
+        ShortlinkCreateResp,
+        TagStats,
+        VariantStats,
+    }, 
+    models::{
+        idempotency::Idempotency,
+        import_job::ImportJob,
+        tag::Tag,
+        link::{
+            CachedLink,
+            ClickQuota,
+            Link,
+            LinkCursor,
+            LinkView,
+            LinkChanges,
+            LinkPreview,
+            NewLink,
+            LINK_STATUS_ACTIVE,
+            LINK_STATUS_PAUSED,
+        },
+        user::User,
+    }, 
     state::AppState
 };
-use crate::services::background_jobs::BackgroundJob;
+use crate::services::{
+    background_jobs::{BackgroundJob, MetadataJob},
+    notifier::ExpiryReminder,
+    short_code::ShortCodeGenerator,
+    transfer,
+    destination::build_destination,
+    targeting::{choose_variant, detect_platform},
+};
+
+
+/// 导出时每批读取的条数
+const EXPORT_BATCH_SIZE: u64 = 500;
+
+/// 永久跳转被浏览器缓存后的统计提示
+const PERMANENT_REDIRECT_WARNING: &str =
+    "Permanent redirects (301/308) are cached by browsers; repeat visits skip the server and are not counted in stats";
+
+
+/// 短码解析结果
+#[derive(Debug)]
+pub enum LinkResolution {
+    /// 跳转到长链；max_age 为永久跳转允许浏览器缓存的秒数，0 表示不允许缓存
+    Redirect {
+        url: String,
+        redirect_type: RedirectType,
+        max_age: u64,
+    },
+    /// 需要输入访问密码
+    PasswordRequired,
+    /// 开启了中间页且访问者尚未确认：展示目标地址（设置了访问密码时为 None），未计点击
+    Interstitial {
+        destination: Option<String>,
+        preview: LinkPreview,
+    },
+}
+
+
+/// 访问者信息，用于按平台跳转、密码错误限流和访问日志
+#[derive(Debug)]
+pub struct Visitor {
+    pub ip: String,
+    pub user_agent: String,
+    pub referer: String,
+}
+
+
+/// 短链无法跳转时的处理方式
+#[derive(Debug)]
+pub enum LinkUnavailable {
+    /// 已过期且设置了过期跳转地址
+    Fallback(String),
+    /// 展示错误页
+    Page(ErrorPageKind),
+}
 
 
 pub struct ShortlinkService;
 
 impl ShortlinkService {
+
+    /// 规范化长 URL：scheme/host 小写、去掉默认端口和片段
+    /// 解析失败时退化为去除首尾空白的原始字符串
+    fn normalize_url(long_url: &str) -> String {
+        match Url::parse(long_url.trim()) {
+            Ok(mut url) => {
+                url.set_fragment(None);
+                if url.query() == Some("") {
+                    url.set_query(None);
+                }
+                url.to_string()
+            },
+            Err(_) => long_url.trim().to_string(),
+        }
+    }
+
+    /// 规范化长 URL 的 SHA-256（十六进制）
+    fn url_hash(long_url: &str) -> String {
+        format!("{:x}", Sha256::digest(Self::normalize_url(long_url).as_bytes()))
+    }
+
+    /// 规范化标签：去掉首尾空白，按不区分大小写去重，保留首次出现的写法
+    fn normalize_tags(tags: Option<&[String]>) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::new();
+        for tag in tags.unwrap_or_default() {
+            let tag = tag.trim();
+            if !normalized.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+                normalized.push(tag.to_string());
+            }
+        }
+        normalized
+    }
+
+    /// 拼接对外短链
+    pub(crate) fn short_url(base: &str, short_code: &str) -> String {
+        format!("{}/s/{}", base.trim_end_matches('/'), short_code)
+    }
+
+    /// 请求是否参与去重：显式指定了有效期（ttl 或 permanent）时不复用已有短链，
+    /// 否则复用的短链与请求的有效期不一致
+    pub fn dedupe_enabled(req: &ShortlinkCreateReq, dedupe_default: bool) -> bool {
+        req.dedupe.unwrap_or(dedupe_default)
+            && req.ttl.is_none()
+            && req.permanent.is_none_or(|p| !p)
+    }
+
+    /// 在事务内创建一条短链，返回 (短码, 是否新建)
+    /// dedupe 为 true 且未指定自定义短码时，复用该用户以去重方式创建的相同 URL 的未过期短链
+    /// 自定义短码已存在时返回 CONFLICT，由调用方决定如何对外呈现
+    async fn create_in_tx(
+        tx: &mut Transaction<'_, MySql>,
+        generator: &dyn ShortCodeGenerator,
+        link: &NewLink<'_>,
+        dedupe: bool,
+        user_id: u64,
+    ) -> Result<(String, bool), (StatusCode, String)> {
+        let long_url_hash = Self::url_hash(link.long_url);
+
+        // 去重：仅对普通短链生效，自定义短码、访问限制或跳转规则等属性不同的短链不复用
+        let plain = link.short_code.is_none()
+            && link.activate_at.is_none()
+            && link.max_clicks.is_none()
+            && link.password_hash.is_none()
+            && link.targets.is_none_or(|t| t.is_empty())
+            && link.variants.is_none()
+            && !link.forward_query
+            && link.utm.is_none_or(|u| u.is_empty())
+            && link.redirect_type.is_none()
+            && !link.interstitial
+            && link.tags.is_empty()
+            && link.title.is_none()
+            && link.note.is_none()
+            && link.fallback_url.is_none();
+        // 先插入再查询：(user_id, dedupe_hash) 唯一约束让并发的相同请求在插入时串行，
+        // 后到的请求等先到的提交后冲突，再复用其短链
+        let dedupe_hash = (dedupe && plain).then_some(long_url_hash.as_str());
+
+        // 插入长 URL
+        let insert_sql = match Link::insert_long_url(
+            tx, 
+            link,
+            &long_url_hash,
+            dedupe_hash,
+            user_id
+        ).await {
+            Ok(r) => r,
+            Err((StatusCode::CONFLICT, _)) => {
+                if let Some(existing) = Link::find_short_code_by_url_hash(
+                    tx,
+                    user_id,
+                    &long_url_hash,
+                ).await? {
+                    return Ok((existing, false));
+                }
+                // 已有的去重短链已过期、删除或被修改，不再复用，让出去重标记后重新插入
+                Link::release_dedupe_hash(tx, user_id, &long_url_hash).await?;
+                Link::insert_long_url(tx, link, &long_url_hash, dedupe_hash, user_id)
+                    .await
+                    .map_err(|e| match e {
+                        (StatusCode::CONFLICT, msg) => {
+                            warn!("create_shortlink: 去重短链并发写入冲突: user_id={}", user_id);
+                            (StatusCode::INTERNAL_SERVER_ERROR, msg)
+                        },
+                        e => e,
+                    })?
+            },
+            Err(e) => return Err(e),
+        };
     
-    /// Base62 编码函数
-    fn encode_base62(mut id: u64) -> String {
-        const BASE62_CHARS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
-        let mut buf = Vec::new();
-        while id > 0 {
-            buf.push(BASE62_CHARS[(id % 62) as usize]);
-            id /= 62;
+        let id = insert_sql.last_insert_id();
+
+        if !link.tags.is_empty() {
+            Tag::set_link_tags(tx, id, user_id, link.tags).await?;
+        }
+
+        if let Some(user_short_code) = link.short_code {
+            // 直接尝试写入；若违反 UNIQUE 约束， update_short_code 会返回 CONFLICT
+            Link::update_short_code(tx, id, user_short_code).await?;
+            return Ok((user_short_code.to_string(), true));
+        }
+
+        // 尝试最多 100 次自动生成；遇到唯一键冲突就换一个新码
+        for attempt in 0..100 {
+            let candidate = generator.generate(id, attempt);
+            match Link::update_short_code(tx, id, &candidate).await {
+                Ok(_) => return Ok((candidate, true)),
+                Err((StatusCode::CONFLICT, _)) => continue, // 短码碰撞，重试
+                Err(e) => return Err(e),
+            }
         }
-        if buf.is_empty() {
-            buf.push(b'0');
+
+        warn!("create_shortlink: 100 次自动生成短码均碰撞, 无法生成唯一短码");
+        Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Unable to generate unique short code".into(),
+        ))
+    }
+
+    /// 校验短链有效时间，未传时使用最小有效时间
+    pub fn check_ttl(
+        ttl: Option<i64>,
+        min_ttl: i64,
+        max_ttl: i64,
+    ) -> Result<i64, (StatusCode, String)> {
+        match ttl {
+            Some(ttl) if ttl < min_ttl || ttl > max_ttl => Err((
+                StatusCode::BAD_REQUEST, 
+                format!("TTL must be between {} and {}", min_ttl, max_ttl)
+            )),
+            Some(ttl) => Ok(ttl),
+            None => Ok(min_ttl),
         }
-        buf.reverse();
-        String::from_utf8(buf).unwrap()
+    }
+
+    /// 校验自定义短码（字符集、长度、保留字、敏感词）
+    fn check_custom_code(
+        state: &AppState,
+        short_code: &str,
+        user_id: u64,
+    ) -> Result<(), (StatusCode, String)> {
+        state.code_policy.check(short_code).map_err(|msg| {
+            warn!("check_custom_code: 自定义短码不合法: user_id={}, short_code={}, reason={}", user_id, short_code, msg);
+            (StatusCode::BAD_REQUEST, msg)
+        })
+    }
+
+    /// 校验目标地址安全策略：长链、按平台跳转规则、分流目标和过期跳转地址都要通过
+    fn check_destinations(
+        state: &AppState,
+        url: Option<&str>,
+        targets: Option<&LinkTargets>,
+        variants: Option<&[LinkVariant]>,
+        fallback_url: Option<&str>,
+        user_id: u64,
+    ) -> Result<(), (StatusCode, String)> {
+        url.into_iter()
+            .chain(fallback_url)
+            .chain(targets.into_iter().flat_map(LinkTargets::urls))
+            .chain(variants.into_iter().flatten().map(|v| v.url.as_str()))
+            .try_for_each(|url| {
+                state.url_policy.check(url).map_err(|msg| {
+                    warn!("check_destinations: 目标地址不允许: user_id={}, url={}, reason={}", user_id, url, msg);
+                    (StatusCode::BAD_REQUEST, msg)
+                })
+            })
+    }
+
+    /// 解析创建时的有效时间，返回 None 表示永久短链
+    /// permanent 或显式 ttl: null 都表示永久，仅对有权限的账号生效，无权限时返回 403
+    pub fn resolve_ttl(
+        ttl: Option<Option<i64>>,
+        permanent: bool,
+        allow_permanent: bool,
+        min_ttl: i64,
+        max_ttl: i64,
+    ) -> Result<Option<i64>, (StatusCode, String)> {
+        if permanent || ttl == Some(None) {
+            if !allow_permanent {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "Permanent links are not allowed for this account".into(),
+                ));
+            }
+            if let Some(Some(_)) = ttl {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    "ttl cannot be set on a permanent link".into(),
+                ));
+            }
+            return Ok(None);
+        }
+
+        Self::check_ttl(ttl.flatten(), min_ttl, max_ttl).map(Some)
+    }
+
+    /// 访问密码加密（argon2，与账号密码一致）
+    fn hash_link_password(password: &str) -> Result<String, (StatusCode, String)> {
+        let salt = SaltString::generate(&mut OsRng);
+        let hashed = Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|e| {
+                warn!("hash_link_password: password encryption failed: err={}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Password encryption failed: {}", e)
+                )
+            })?
+            .to_string();
+        Ok(hashed)
+    }
+
+    /// 生效时间，不晚于当前时间时视为立即生效
+    fn activate_at(activate_at: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        activate_at.filter(|t| *t > Utc::now())
+    }
+
+    /// 过期时间，None 表示永久；有效时间从生效时间起算
+    fn expire_at(ttl: Option<i64>, activate_at: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+        ttl.map(|ttl| activate_at.unwrap_or_else(Utc::now) + chrono::Duration::seconds(ttl))
+    }
+
+    /// 缓存时间不超过 Redis 最大存储时间，永久短链按最大存储时间缓存
+    fn cache_ttl(ttl: Option<i64>, redis_max_ttl: i64) -> i64 {
+        match ttl {
+            Some(ttl) if ttl < redis_max_ttl => ttl,
+            _ => redis_max_ttl,
+        }
+    }
+
+    /// 跳转状态码，未指定时使用配置默认值
+    fn redirect_type(redirect_type: Option<RedirectType>, default: u16) -> RedirectType {
+        redirect_type
+            .or_else(|| RedirectType::try_from(default).ok())
+            .unwrap_or(RedirectType::SeeOther)
     }
 
     /// 创建短链
+    /// dedupe 为 true 且未指定自定义短码时，复用该用户相同 URL 的未过期短链
     pub async fn create_shortlink(
         state: &AppState,
-        long_url: &str,
-        user_short_code: Option<String>,
-        ttl: i64,
+        payload: &ShortlinkCreateReq,
+        ttl: Option<i64>,
+        dedupe: bool,
         user_id: u64
-    ) -> Result<String, (StatusCode, String)> {
-        // todo 是否需要做幂等校验？
-        let expire_at = chrono::Utc::now() + chrono::Duration::seconds(ttl);
+    ) -> Result<ShortlinkCreateResp, (StatusCode, String)> {
+        let user_short_code = payload.short_code.as_deref();
+        // 校验自定义短码
+        if let Some(code) = user_short_code {
+            Self::check_custom_code(state, code, user_id)?;
+        }
+        Self::check_destinations(
+            state,
+            Some(&payload.url),
+            payload.targets.as_ref(),
+            payload.variants.as_deref(),
+            payload.fallback_url.as_deref(),
+            user_id,
+        )?;
+
+        let password_hash = payload.password
+            .as_deref()
+            .map(Self::hash_link_password)
+            .transpose()?;
+        let activate_at = Self::activate_at(payload.activate_at);
+        let tags = Self::normalize_tags(payload.tags.as_deref());
+        let link = NewLink {
+            long_url: &payload.url,
+            short_code: user_short_code,
+            activate_at,
+            expire_at: Self::expire_at(ttl, activate_at),
+            max_clicks: payload.max_clicks,
+            password_hash: password_hash.as_deref(),
+            targets: payload.targets.as_ref(),
+            variants: payload.variants.as_deref(),
+            forward_query: payload.forward_query.unwrap_or(false),
+            utm: payload.utm.as_ref(),
+            redirect_type: payload.redirect_type,
+            interstitial: payload.interstitial.unwrap_or(false),
+            qr_public: payload.qr_public.unwrap_or(false),
+            tags: &tags,
+            title: payload.title.as_deref(),
+            note: payload.note.as_deref(),
+            fallback_url: payload.fallback_url.as_deref(),
+        };
         // 开启事务
         let mut tx = state
             .mysql_pool
@@ -48,82 +423,386 @@ impl ShortlinkService {
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Begin error: {}", e))
             })?;
 
-        // 插入长 URL
-        let insert_sql = Link::insert_long_url(
-            &mut tx, 
-            long_url,
-            expire_at,
-            user_id
-        ).await?;
+        let (short_code, created) = match Self::create_in_tx(
+            &mut tx,
+            state.code_generator.as_ref(),
+            &link,
+            dedupe,
+            user_id,
+        ).await {
+            Ok(r) => r,
+            Err((StatusCode::CONFLICT, _)) => {
+                warn!("create_shortlink: 用户自定义短码已存在: user_id={}, short_code={:?}", user_id, user_short_code);
+                // 用户自定义短码已存在
+                return Err((StatusCode::BAD_REQUEST, "Short code already exists".into()));
+            }
+            Err(e) => return Err(e),
+        };
+
+        tx.commit().await.map_err(|e| {
+            warn!("create_shortlink: DB Commit error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+        })?;
     
-        let id = insert_sql.last_insert_id();
-        let mut short_code = String::new();
+        // 判断过期时间是否大于设置的redis最大存储时间
+        // 大于则设置为最大存储时间
+        let config = state.config.read().await;
 
-        if let Some(user_short_code) = user_short_code {
-            short_code = user_short_code;
+        // 复用的已有短链无需重新设置缓存；未生效的短链不提前缓存，由生效后首次访问回源写入
+        if created && link.activate_at.is_none() {
+            // 设置点击量和缓存
+            if let Err(e) = state.bg_redis_tx.try_send(BackgroundJob::SetClickCount {
+                short_code: short_code.clone(),
+                cached: CachedLink {
+                    long_url: payload.url.clone(),
+                    max_clicks: payload.max_clicks,
+                    password_hash: password_hash.clone(),
+                    targets: payload.targets.clone().filter(|t| !t.is_empty()),
+                    variants: payload.variants.clone(),
+                    forward_query: payload.forward_query.unwrap_or(false),
+                    utm: payload.utm.clone().filter(|u| !u.is_empty()),
+                    redirect_type: payload.redirect_type,
+                    interstitial: payload.interstitial.unwrap_or(false),
+                    expire_at: link.expire_at.map(|t| t.timestamp()),
+                    created_at: Some(Utc::now().timestamp()),
+                    activate_at: None,
+                },
+                cache_ttl: Self::cache_ttl(ttl, config.redis_max_ttl),
+            }) {
+                // 队列已满时放弃预热，首次访问回源 MySQL 后写入缓存
+                warn!("create_shortlink: bg_redis_tx try_send failed: short_code={}, err={}", short_code, e);
+            }
+        }
 
-            // 直接尝试写入；若违反 UNIQUE 约束， update_short_code 会返回 CONFLICT
-            match Link::update_short_code(&mut tx, id, &short_code).await {
-                Ok(_) => {}
-                Err((StatusCode::CONFLICT, _)) => {
-                    warn!("create_shortlink: 用户自定义短码已存在: user_id={}, short_code={}", user_id, short_code);
-                    // 用户自定义短码已存在
-                    return Err((StatusCode::BAD_REQUEST, "Short code already exists".into()));
+        if created && config.link_metadata_enabled {
+            Self::enqueue_metadata_fetch(state, &short_code, &payload.url);
+        }
+
+        let redirect_type = Self::redirect_type(payload.redirect_type, config.redirect_type_default);
+        Ok(ShortlinkCreateResp {
+            short_url: Self::short_url(&config.base_url(), &short_code),
+            warning: redirect_type
+                .is_permanent()
+                .then(|| PERMANENT_REDIRECT_WARNING.to_string()),
+        })
+    }
+
+    /// 投递目标页元数据抓取作业；队列已满时放弃，元数据只是展示用途
+    /// 批量创建不抓取，避免短时间内对外发出大量请求
+    fn enqueue_metadata_fetch(state: &AppState, short_code: &str, long_url: &str) {
+        if let Err(e) = state.metadata_tx.try_send(MetadataJob {
+            short_code: short_code.to_string(),
+            long_url: long_url.to_string(),
+        }) {
+            warn!("enqueue_metadata_fetch: metadata_tx try_send failed: short_code={}, err={}", short_code, e);
+        }
+    }
+
+    /// 批量创建短链
+    /// 每条单独校验，按 chunk 分事务写入；单条失败只回滚该条的保存点，不影响同批其它条目
+    pub async fn create_shortlinks_batch(
+        state: &AppState,
+        items: &[ShortlinkCreateReq],
+        user: &User,
+    ) -> Result<Vec<BatchItemResult>, (StatusCode, String)> {
+        let user_id = user.id;
+        let (min_ttl, max_ttl, redis_max_ttl, dedupe_default, chunk_size, base) = {
+            let config = state.config.read().await;
+            (
+                config.shortlink_min_ttl,
+                config.shortlink_max_ttl,
+                config.redis_max_ttl,
+                config.shortlink_dedupe_default,
+                config.shortlink_batch_chunk_size.max(1),
+                config.base_url(),
+            )
+        };
+
+        let mut results = Vec::with_capacity(items.len());
+
+        for (chunk_idx, chunk) in items.chunks(chunk_size).enumerate() {
+            let offset = chunk_idx * chunk_size;
+            // 本 chunk 内创建成功、等待提交的条目：(结果下标, 短码, 缓存内容, ttl, 是否预热缓存)
+            let mut pending = Vec::with_capacity(chunk.len());
+
+            // 开启事务失败时本 chunk 全部标记为失败，之前已提交的 chunk 结果照常返回
+            let mut tx = match state.mysql_pool.begin().await {
+                Ok(tx) => tx,
+                Err(e) => {
+                    warn!("create_shortlinks_batch: DB Begin error: {}", e);
+                    for i in 0..chunk.len() {
+                        results.push(BatchItemResult::failed(offset + i, BatchErrorCode::Internal, format!("DB Begin error: {}", e)));
+                    }
+                    continue;
                 }
-                Err(e) => return Err(e),
-            }
-        } else {
-            // 尝试最多 100 次自动生成；遇到唯一键冲突就换一个新码
-            for i in 0..100 {
-                let candidate = Self::encode_base62(id + i as u64);
-                match Link::update_short_code(&mut tx, id, &candidate).await {
-                    Ok(_) => {
-                        short_code = candidate;
-                        break;
+            };
+
+            for (i, item) in chunk.iter().enumerate() {
+                let index = offset + i;
+
+                if let Err(e) = item.validate() {
+                    let fields = e.errors();
+                    let code = if fields.contains_key("max_clicks") {
+                        BatchErrorCode::InvalidMaxClicks
+                    } else if fields.contains_key("password") {
+                        BatchErrorCode::InvalidPassword
+                    } else if fields.contains_key("variants") {
+                        BatchErrorCode::InvalidVariants
+                    } else if fields.contains_key("tags") {
+                        BatchErrorCode::InvalidTags
+                    } else if fields.contains_key("title") {
+                        BatchErrorCode::InvalidTitle
+                    } else {
+                        BatchErrorCode::InvalidUrl
+                    };
+                    results.push(BatchItemResult::failed(index, code, e.to_string()));
+                    continue;
+                }
+
+                if let Some(code) = item.short_code.as_deref() {
+                    if let Err((_, msg)) = Self::check_custom_code(state, code, user_id) {
+                        results.push(BatchItemResult::failed(index, BatchErrorCode::InvalidCode, msg));
+                        continue;
+                    }
+                }
+
+                if let Err((_, msg)) = Self::check_destinations(
+                    state,
+                    Some(&item.url),
+                    item.targets.as_ref(),
+                    item.variants.as_deref(),
+                    item.fallback_url.as_deref(),
+                    user_id,
+                ) {
+                    results.push(BatchItemResult::failed(index, BatchErrorCode::UrlNotAllowed, msg));
+                    continue;
+                }
+
+                let ttl = match Self::resolve_ttl(
+                    item.ttl,
+                    item.permanent.unwrap_or(false),
+                    user.allow_permanent,
+                    min_ttl,
+                    max_ttl,
+                ) {
+                    Ok(ttl) => ttl,
+                    Err((status, msg)) => {
+                        let code = if status == StatusCode::FORBIDDEN {
+                            BatchErrorCode::PermanentNotAllowed
+                        } else {
+                            BatchErrorCode::TtlOutOfRange
+                        };
+                        results.push(BatchItemResult::failed(index, code, msg));
+                        continue;
                     }
-                    Err((StatusCode::CONFLICT, _)) => continue, // 短码碰撞，重试
+                };
+
+                let password_hash = match item.password.as_deref().map(Self::hash_link_password).transpose() {
+                    Ok(hash) => hash,
+                    Err((_, msg)) => {
+                        results.push(BatchItemResult::failed(index, BatchErrorCode::Internal, msg));
+                        continue;
+                    }
+                };
+
+                // 每条使用独立保存点
+                let mut sp = match (&mut tx).begin().await {
+                    Ok(sp) => sp,
                     Err(e) => {
-                        return Err(e);
+                        warn!("create_shortlinks_batch: DB Savepoint error: {}", e);
+                        results.push(BatchItemResult::failed(index, BatchErrorCode::Internal, format!("DB Savepoint error: {}", e)));
+                        continue;
                     }
+                };
+
+                let activate_at = Self::activate_at(item.activate_at);
+                let tags = Self::normalize_tags(item.tags.as_deref());
+                let link = NewLink {
+                    long_url: &item.url,
+                    short_code: item.short_code.as_deref(),
+                    activate_at,
+                    expire_at: Self::expire_at(ttl, activate_at),
+                    max_clicks: item.max_clicks,
+                    password_hash: password_hash.as_deref(),
+                    targets: item.targets.as_ref(),
+                    variants: item.variants.as_deref(),
+                    forward_query: item.forward_query.unwrap_or(false),
+                    utm: item.utm.as_ref(),
+                    redirect_type: item.redirect_type,
+                    interstitial: item.interstitial.unwrap_or(false),
+                    qr_public: item.qr_public.unwrap_or(false),
+                    tags: &tags,
+                    title: item.title.as_deref(),
+                    note: item.note.as_deref(),
+                    fallback_url: item.fallback_url.as_deref(),
+                };
+                let expire_at = link.expire_at;
+                match Self::create_in_tx(
+                    &mut sp,
+                    state.code_generator.as_ref(),
+                    &link,
+                    Self::dedupe_enabled(item, dedupe_default),
+                    user_id,
+                ).await {
+                    Ok((short_code, created)) => {
+                        if let Err(e) = sp.commit().await {
+                            warn!("create_shortlinks_batch: DB Release savepoint error: {}", e);
+                            results.push(BatchItemResult::failed(index, BatchErrorCode::Internal, format!("DB Commit error: {}", e)));
+                            continue;
+                        }
+                        results.push(BatchItemResult::succeeded(index, Self::short_url(&base, &short_code)));
+                        let cached = CachedLink {
+                            long_url: item.url.clone(),
+                            max_clicks: item.max_clicks,
+                            password_hash,
+                            targets: item.targets.clone().filter(|t| !t.is_empty()),
+                            variants: item.variants.clone(),
+                            forward_query: item.forward_query.unwrap_or(false),
+                            utm: item.utm.clone().filter(|u| !u.is_empty()),
+                            redirect_type: item.redirect_type,
+                            interstitial: item.interstitial.unwrap_or(false),
+                            expire_at: expire_at.map(|t| t.timestamp()),
+                            created_at: Some(Utc::now().timestamp()),
+                            activate_at: None,
+                        };
+                        let warm = created && activate_at.is_none();
+                        pending.push((results.len() - 1, short_code, cached, ttl, warm));
+                    },
+                    Err((status, msg)) => {
+                        if let Err(e) = sp.rollback().await {
+                            warn!("create_shortlinks_batch: DB Rollback savepoint error: {}", e);
+                        }
+                        let code = if status == StatusCode::CONFLICT {
+                            BatchErrorCode::CodeTaken
+                        } else {
+                            BatchErrorCode::Internal
+                        };
+                        results.push(BatchItemResult::failed(index, code, msg));
+                    },
                 }
             }
 
-            if short_code.is_empty() {
-                warn!("create_shortlink: 100 次自动生成短码均碰撞, 无法生成唯一短码");
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "Unable to generate unique short code".into(),
-                ));
+            // 提交失败时本 chunk 已成功的条目全部标记为失败
+            if let Err(e) = tx.commit().await {
+                warn!("create_shortlinks_batch: DB Commit error: {}", e);
+                for (pos, ..) in &pending {
+                    let index = results[*pos].index;
+                    results[*pos] = BatchItemResult::failed(index, BatchErrorCode::Internal, format!("DB Commit error: {}", e));
+                }
+                continue;
+            }
+
+            // 设置点击量和缓存；队列已满时放弃预热，首次访问回源 MySQL 后写入缓存
+            // 不等待队列空位，避免批量写入占满队列后阻塞跳转的统计投递
+            for (_, short_code, cached, ttl, warm) in pending {
+                if !warm {
+                    continue;
+                }
+                if let Err(e) = state.bg_redis_tx.try_send(BackgroundJob::SetClickCount {
+                    short_code,
+                    cached,
+                    cache_ttl: Self::cache_ttl(ttl, redis_max_ttl),
+                }) {
+                    warn!("create_shortlinks_batch: bg_redis_tx try_send failed: {}", e);
+                }
             }
         }
 
-        tx.commit().await.map_err(|e| {
-            warn!("create_shortlink: DB Commit error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+        Ok(results)
+    }
+
+    /// 带 Idempotency-Key 的创建短链
+    /// 同一幂等键的重试直接回放首次结果，请求体不一致时返回 422
+    pub async fn create_shortlink_idempotent(
+        state: &AppState,
+        payload: &ShortlinkCreateReq,
+        ttl: Option<i64>,
+        dedupe: bool,
+        user_id: u64,
+        idempotency_key: &str,
+    ) -> Result<ShortlinkCreateResp, (StatusCode, String)> {
+        let body = serde_json::to_vec(payload).map_err(|e| {
+            warn!("create_shortlink_idempotent: serialize error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Serialize error: {}", e))
         })?;
-    
-        // 判断过期时间是否大于设置的redis最大存储时间
-        // 大于则设置为最大存储时间
-        let config = state.config.read().await;
-        let redis_max_ttl = config.redis_max_ttl;
-        let cache_ttl = if ttl > redis_max_ttl {
-            redis_max_ttl
-        } else {
-            ttl
-        };
+        let fingerprint = format!("{:x}", Sha256::digest(&body));
+        let key = format!("idempotency:{}:{}", user_id, idempotency_key);
+        let idempotency_ttl = state.config.read().await.idempotency_ttl;
+
+        // 抢占幂等键，连接用完即释放，避免创建期间占用连接池
+        {
+            let mut conn = state.redis_pool.get().await.map_err(|e| {
+                warn!("create_shortlink_idempotent: Redis 获取连接失败: err={}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+            })?;
+
+            if let Some(record) = Idempotency::begin(
+                &mut conn,
+                &key,
+                &fingerprint,
+                idempotency_ttl,
+            ).await? {
+                if record.fingerprint != fingerprint {
+                    warn!("create_shortlink_idempotent: 幂等键请求体不一致: user_id={}, key={}", user_id, idempotency_key);
+                    return Err((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        "Idempotency-Key was already used with a different request body".into(),
+                    ));
+                }
+
+                return match record.response {
+                    Some(resp) => serde_json::from_value(resp).map_err(|e| {
+                        warn!("create_shortlink_idempotent: deserialize error: {}", e);
+                        (StatusCode::INTERNAL_SERVER_ERROR, format!("Deserialize error: {}", e))
+                    }),
+                    None => {
+                        warn!("create_shortlink_idempotent: 首次请求仍在处理: user_id={}, key={}", user_id, idempotency_key);
+                        Err((
+                            StatusCode::CONFLICT,
+                            "A request with this Idempotency-Key is still in progress".into(),
+                        ))
+                    },
+                };
+            }
+        }
 
-        let short_code2 = short_code.clone();
-        let long_url2 = long_url.to_string();
+        let result = Self::create_shortlink(
+            state,
+            payload,
+            ttl,
+            dedupe,
+            user_id,
+        ).await;
 
-        // 设置点击量和缓存
-        state.bg_redis_tx.try_send(BackgroundJob::SetClickCount {
-            short_code: short_code2,
-            long_url: long_url2,
-            cache_ttl,
-        }).expect("create_shortlink: bg_redis_tx try_send failed");
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("create_shortlink_idempotent: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
 
-        let base = config.addr.clone();
-        Ok(format!("{}/s/{}", base.trim_end_matches('/'), short_code))
+        match result {
+            Ok(resp) => {
+                // 短链已创建成功，记录失败只告警，不影响本次返回
+                match serde_json::to_value(&resp) {
+                    Ok(value) => {
+                        if let Err(e) = Idempotency::complete(
+                            &mut conn,
+                            &key,
+                            &fingerprint,
+                            value,
+                            idempotency_ttl,
+                        ).await {
+                            warn!("create_shortlink_idempotent: 记录幂等结果失败: {:?}", e);
+                        }
+                    },
+                    Err(e) => warn!("create_shortlink_idempotent: serialize error: {}", e),
+                }
+                Ok(resp)
+            },
+            Err(e) => {
+                Idempotency::release(&mut conn, &key).await;
+                Err(e)
+            },
+        }
     }
 
     /// 增加点击数和访问日志
@@ -134,6 +813,7 @@ impl ShortlinkService {
         ip: String,
         user_agent: String,
         referer: String,
+        variant: Option<u16>,
     ) {
             Link::log_visit_to_stream(
                 conn,
@@ -142,6 +822,7 @@ impl ShortlinkService {
                 &ip,
                 &user_agent,
                 &referer,
+                variant,
             ).await;
 
             Link::in_click_count(
@@ -150,74 +831,322 @@ impl ShortlinkService {
             ).await;
     }
 
+    /// MySQL 回溯：校验过期、生效、暂停与访问次数，初始化访问额度并回写缓存
+    async fn load_link(
+        state: &AppState,
+        conn: &mut Connection,
+        short_code: &str,
+    ) -> Result<CachedLink, (StatusCode, String)> {
+        let target = Link::get_logn_url_from_mysql(
+            &state.mysql_pool, 
+            short_code
+        ).await?;
+
+        // 剩余有效时间(None为永久)
+        let remaining = match target.expire_at {
+            Some(expire) => {
+                let now_ts = chrono::Utc::now().timestamp();
+                let ttl = expire.and_utc().timestamp() - now_ts;
+                // 已过期
+                if ttl <= 0 {
+                    warn!("get_long_url: link expired: short_code={}", short_code);
+                    return Err((StatusCode::NOT_FOUND, "Link expired".into()));
+                }
+                Some(ttl)
+            },
+            None => None,
+        };
+
+        // 尚未生效：不跳转也不回写缓存
+        if target.activate_at.is_some_and(|t| t.and_utc() > Utc::now()) {
+            warn!("get_long_url: link not yet active: short_code={}", short_code);
+            return Err((StatusCode::FORBIDDEN, "Link not yet active".into()));
+        }
+
+        // 已暂停：不跳转也不回写缓存
+        if target.status == LINK_STATUS_PAUSED {
+            warn!("get_long_url: link paused: short_code={}", short_code);
+            return Err((StatusCode::GONE, "Link paused".into()));
+        }
+
+        // 已同步的点击量达到上限，无需再访问 Redis
+        if target.max_clicks.is_some_and(|max| target.click_count >= max as u64) {
+            warn!("get_long_url: link click limit reached: short_code={}", short_code);
+            return Err((StatusCode::GONE, "Link click limit reached".into()));
+        }
+
+        let (redis_min_cache_ttl, redis_max_ttl) = {
+            let config = state.config.read().await;
+            (config.redis_min_cache_ttl, config.redis_max_ttl)
+        };
+        let cache_ttl = Self::cache_ttl(remaining, redis_max_ttl);
+
+        // 限制访问次数：按剩余额度初始化 Redis 计数（已存在时不覆盖）
+        if let Some(max_clicks) = target.max_clicks {
+            Link::init_click_quota(
+                conn,
+                short_code,
+                max_clicks as i64 - target.click_count as i64,
+                cache_ttl,
+            ).await?;
+        }
+
+        let cached = target.to_cached();
+
+        // 永久短链，或剩余时间大于redis缓存最小剩余有效期时回写缓存
+        if remaining.is_none_or(|ttl| ttl > redis_min_cache_ttl) {
+            Link::set_shortlink(
+                conn,
+                short_code,
+                &cached,
+                cache_ttl,
+            ).await?;
+        }
+
+        Ok(cached)
+    }
+
+    /// 校验访问密码，错误次数按 IP + 短码限流
+    async fn verify_link_password(
+        state: &AppState,
+        conn: &mut Connection,
+        short_code: &str,
+        ip: &str,
+        password_hash: &str,
+        password: &str,
+    ) -> Result<(), (StatusCode, String)> {
+        let (fail_limit, fail_ttl) = {
+            let config = state.config.read().await;
+            (config.link_password_fail_limit, config.link_password_fail_ttl)
+        };
+        let fail_key = format!("link_pwd_fail:{}:{}", ip, short_code);
+
+        Link::can_try_password(conn, &fail_key, fail_limit).await?;
+
+        let parsed_hash = PasswordHash::new(password_hash)
+            .map_err(|_| {
+                warn!("verify_link_password: password hash parse failed: short_code={}", short_code);
+                (StatusCode::INTERNAL_SERVER_ERROR, "Password hash parse failed".into())
+            })?;
+
+        if Argon2::default().verify_password(password.as_bytes(), &parsed_hash).is_err() {
+            warn!("verify_link_password: invalid password: short_code={}, ip={}", short_code, ip);
+            Link::record_password_fail(conn, &fail_key, fail_ttl).await?;
+            return Err((StatusCode::UNAUTHORIZED, "Invalid password".into()));
+        }
+
+        Link::password_success(conn, &fail_key).await
+    }
+
     /// 获取长链
+    /// 设置了访问密码的短链在未提供密码时返回 PasswordRequired，不计点击
+    /// 开启中间页的短链在访问者确认（confirmed）前只返回 Interstitial，不计点击
+    /// query 为访问时携带的原始查询参数，仅开启透传的短链使用
     pub async fn get_long_url(
-        ip: &str,
-        user_agent: &str,
-        referer: &str,
+        visitor: &Visitor,
         state: &AppState,
         short_code: &str,
-    ) -> Result<String, (StatusCode, String)> {
+        query: Option<&str>,
+        password: Option<&str>,
+        confirmed: bool,
+    ) -> Result<LinkResolution, (StatusCode, String)> {
         // 随机选择一个 Redis 连接
         let mut conn = state.redis_pool.get().await.map_err(|e| {
             warn!("get_long_url: Redis 获取连接失败: err={}", e);
             (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
         })?;
         
-        // redis 命中
-        if let Some(long_url) = Link::get_long_url_from_redis(
+        // redis 命中，未命中时 MySQL 回溯
+        let cached = match Link::get_long_url_from_redis(
             &mut conn, 
             short_code
         ).await? {
-             // 异步推送点击量和访问日志
-            state.bg_redis_tx.try_send(BackgroundJob::PushClickAndLog {
-                short_code: short_code.to_string(),
-                long_url: long_url.clone(),
-                ip: ip.to_string(),
-                user_agent: user_agent.to_string(),
-                referer: referer.to_string(),
-            }).expect("get_long_url: bg_redis_tx try_send failed");
+            Some(cached) => cached,
+            None => Self::load_link(state, &mut conn, short_code).await?,
+        };
+        let query = query.filter(|_| cached.forward_query);
 
-            return Ok(long_url)
+        // 中间页：确认前不校验密码、不消耗访问次数、不记录访问
+        if cached.interstitial && !confirmed {
+            // 早期版本的缓存缺少中间页信息时回源 MySQL
+            let preview = match cached.preview() {
+                Some(preview) => preview,
+                None => Link::find_preview(&state.mysql_pool, short_code).await?,
+            };
+            // 设置了访问密码的短链不展示目标地址；A/B 分流在确认后才选择，这里展示默认长链
+            let destination = cached.password_hash.is_none().then(|| {
+                let long_url = cached.targets
+                    .as_ref()
+                    .and_then(|t| t.for_platform(detect_platform(&visitor.user_agent)))
+                    .unwrap_or(cached.long_url.as_str());
+                build_destination(long_url, query, cached.utm.as_ref())
+            });
+            return Ok(LinkResolution::Interstitial { destination, preview });
         }
 
-        // MySQL 回溯
-        let (long_url, expire_opt) = Link::get_logn_url_from_mysql(
-            &state.mysql_pool, 
-            short_code
-        ).await?;
+        // 访问密码
+        if let Some(password_hash) = cached.password_hash.as_deref() {
+            let Some(password) = password else {
+                return Ok(LinkResolution::PasswordRequired);
+            };
+            Self::verify_link_password(
+                state,
+                &mut conn,
+                short_code,
+                &visitor.ip,
+                password_hash,
+                password,
+            ).await?;
+        }
 
-        // 有设置过期时间(None为永久)
-        if let Some(expire) = expire_opt {
-            let now_ts = chrono::Utc::now().timestamp();
-            let ttl = expire.and_utc().timestamp() - now_ts;
-            // 已过期
-            if ttl <= 0 {
-                warn!("get_long_url: link expired: short_code={}", short_code);
-                return Err((StatusCode::NOT_FOUND, "Link expired".into()));
+        // 限制访问次数的短链原子消费额度；额度 key 缺失（过期或被淘汰）时回源重新初始化
+        if cached.max_clicks.is_some() {
+            let mut quota = Link::consume_click_quota(&mut conn, short_code).await?;
+            if quota == ClickQuota::Missing {
+                Self::load_link(state, &mut conn, short_code).await?;
+                quota = Link::consume_click_quota(&mut conn, short_code).await?;
             }
-
-            // 未过期，且剩余时间大于redis缓存最小剩余有效期
-            if ttl > state.config.read().await.redis_min_cache_ttl {
-                Link::set_shortlink(
-                    &mut conn,
-                    short_code,
-                    &long_url,
-                    ttl,
-                ).await?;
+            if quota != ClickQuota::Granted {
+                warn!("get_long_url: link click limit reached: short_code={}", short_code);
+                return Err((StatusCode::GONE, "Link click limit reached".into()));
             }
         }
 
-        // 异步推送点击量和访问日志
-        state.bg_redis_tx.try_send(BackgroundJob::PushClickAndLog {
+        let (redirect_type, max_age) = {
+            let config = state.config.read().await;
+            (
+                Self::redirect_type(cached.redirect_type, config.redirect_type_default),
+                cached.redirect_max_age(config.redirect_permanent_max_age, Utc::now().timestamp()),
+            )
+        };
+
+        // 平台跳转规则优先；未命中时按权重分流，都未配置时使用默认长链
+        let mut variant = None;
+        let long_url = if let Some(url) = cached.targets
+            .as_ref()
+            .and_then(|t| t.for_platform(detect_platform(&visitor.user_agent)))
+        {
+            url.to_string()
+        } else if let Some((i, v)) = cached.variants
+            .as_deref()
+            .and_then(|v| choose_variant(v).map(|i| (i, &v[i])))
+        {
+            variant = Some(i as u16);
+            v.url.clone()
+        } else {
+            cached.long_url
+        };
+
+        // 异步推送点击量和访问日志；队列已满时丢弃本次统计，不影响跳转
+        if let Err(e) = state.bg_redis_tx.try_send(BackgroundJob::PushClickAndLog {
             short_code: short_code.to_string(),
             long_url: long_url.clone(),
-            ip: ip.to_string(),
-            user_agent: user_agent.to_string(),
-            referer: referer.to_string(),
-        }).expect("get_long_url: bg_redis_tx try_send failed");
+            ip: visitor.ip.clone(),
+            user_agent: visitor.user_agent.clone(),
+            referer: visitor.referer.clone(),
+            variant,
+        }) {
+            warn!("get_long_url: bg_redis_tx try_send failed: short_code={}, err={}", short_code, e);
+        }
+
+        // 追加 UTM 模板和透传参数，访问日志仍记录未拼接参数的目标地址
+        let url = build_destination(&long_url, query, cached.utm.as_ref());
+
+        Ok(LinkResolution::Redirect {
+            url,
+            redirect_type,
+            max_age,
+        })
+    }
+
+    /// 跳转失败（404 / 410）后判断展示哪种错误页，或改为跳转到过期跳转地址
+    /// 过期短链被定时任务物理删除后按不存在处理；其它原因（如访问次数用尽）返回 None，沿用原错误
+    pub async fn resolve_unavailable(
+        state: &AppState,
+        short_code: &str,
+    ) -> Result<Option<LinkUnavailable>, (StatusCode, String)> {
+        let Some(link) = Link::find_unavailable(&state.mysql_pool, short_code).await? else {
+            return Ok(Some(LinkUnavailable::Page(ErrorPageKind::NotFound)));
+        };
+
+        if link.expire_at.is_some_and(|t| t.and_utc() <= Utc::now()) {
+            return Ok(Some(match link.fallback_url {
+                Some(url) => LinkUnavailable::Fallback(url),
+                None => LinkUnavailable::Page(ErrorPageKind::Expired),
+            }));
+        }
+        if link.status == LINK_STATUS_PAUSED {
+            return Ok(Some(LinkUnavailable::Page(ErrorPageKind::Paused)));
+        }
+
+        Ok(None)
+    }
+
+    /// 预览短链：只读 MySQL，不计点击、不写访问日志
+    pub async fn preview_link(
+        state: &AppState,
+        short_code: &str,
+    ) -> Result<LinkPreview, (StatusCode, String)> {
+        let preview = Link::find_preview(&state.mysql_pool, short_code).await?;
+
+        if preview.expire_at.is_some_and(|t| t.and_utc() <= Utc::now()) {
+            warn!("preview_link: link expired: short_code={}", short_code);
+            return Err((StatusCode::NOT_FOUND, "Link expired".into()));
+        }
+        if preview.activate_at.is_some_and(|t| t.and_utc() > Utc::now()) {
+            warn!("preview_link: link not yet active: short_code={}", short_code);
+            return Err((StatusCode::FORBIDDEN, "Link not yet active".into()));
+        }
+        if preview.status == LINK_STATUS_PAUSED {
+            warn!("preview_link: link paused: short_code={}", short_code);
+            return Err((StatusCode::GONE, "Link paused".into()));
+        }
+
+        Ok(preview)
+    }
+
+    /// 所有者获取二维码内容（对外短链）
+    pub async fn owned_qr_url(
+        state: &AppState,
+        link_id: u64,
+        user_id: u64,
+    ) -> Result<String, (StatusCode, String)> {
+        let short_code = Link::find_short_code_by_id(&state.mysql_pool, link_id, user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!("owned_qr_url: 短链不存在: user_id={}, link_id={}", user_id, link_id);
+                (StatusCode::NOT_FOUND, "Link not found".to_string())
+            })?;
+
+        let base = state.config.read().await.base_url();
+        Ok(Self::short_url(&base, &short_code))
+    }
+
+    /// 公开获取二维码内容，短链未开启 qr_public 时按不存在处理
+    /// 未生效、已暂停或访问次数用尽的短链与跳转一致返回 403 / 410
+    pub async fn public_qr_url(
+        state: &AppState,
+        short_code: &str,
+    ) -> Result<String, (StatusCode, String)> {
+        let Some(link) = Link::find_public_qr(&state.mysql_pool, short_code).await? else {
+            warn!("public_qr_url: 短码不存在或未开启公开二维码: short_code={}", short_code);
+            return Err((StatusCode::NOT_FOUND, "Short code not found".into()));
+        };
+        if link.activate_at.is_some_and(|t| t.and_utc() > Utc::now()) {
+            warn!("public_qr_url: link not yet active: short_code={}", short_code);
+            return Err((StatusCode::FORBIDDEN, "Link not yet active".into()));
+        }
+        if link.status == LINK_STATUS_PAUSED {
+            warn!("public_qr_url: link paused: short_code={}", short_code);
+            return Err((StatusCode::GONE, "Link paused".into()));
+        }
+        if link.max_clicks.is_some_and(|max| link.click_count >= max as u64) {
+            warn!("public_qr_url: link click limit reached: short_code={}", short_code);
+            return Err((StatusCode::GONE, "Link click limit reached".into()));
+        }
 
-        Ok(long_url)
+        let base = state.config.read().await.base_url();
+        Ok(Self::short_url(&base, short_code))
     }
 
     /// 获取短链列表
@@ -226,14 +1155,235 @@ impl ShortlinkService {
         filter: &LinkQuery,
         limit: u64,
         offset: u64,
-    ) -> Result<(Vec<LinkView>, i64), (StatusCode, String)> {
-        let (links, count) = Link::find_links(
+    ) -> Result<LinkList, (StatusCode, String)> {
+        // 游标只能用于生成它的排序方式
+        let cursor = match filter.cursor.as_deref() {
+            Some(raw) => {
+                let cursor = LinkCursor::decode(raw).map_err(|msg| {
+                    warn!("list_links: 游标不合法: user_id={:?}, cursor={}", filter.user_id, raw);
+                    (StatusCode::BAD_REQUEST, msg)
+                })?;
+                if cursor.key.sort() != filter.sort || cursor.order != filter.order {
+                    warn!("list_links: 游标与排序方式不一致: user_id={:?}, cursor={:?}", filter.user_id, cursor);
+                    return Err((StatusCode::BAD_REQUEST, "Cursor does not match the current sort".into()));
+                }
+                Some(cursor)
+            },
+            None => None,
+        };
+
+        let (mut links, next_cursor) = Link::find_links(
             &state.mysql_pool,
             filter,
+            cursor.as_ref(),
             limit,
             offset,
         ).await?;
-        Ok((links, count))
+        Tag::fill_link_tags(&state.mysql_pool, &mut links).await?;
+
+        // 总数统计开销较大，翻页时缺省不再统计
+        let count = if filter.with_count.unwrap_or(cursor.is_none()) {
+            Some(Link::count_links(&state.mysql_pool, filter).await?)
+        } else {
+            None
+        };
+
+        Ok(LinkList {
+            links,
+            count,
+            next_cursor: next_cursor.map(|c| c.encode()),
+        })
+    }
+
+    /// 导出短链：按 id 分批读取并编码，逐批写入响应体
+    /// 响应开始后出错只能中断输出，客户端会收到不完整的文件
+    pub async fn export_links(
+        state: Arc<AppState>,
+        filter: LinkQuery,
+        format: ExportFormat,
+    ) -> impl Stream<Item = Result<Bytes, io::Error>> + Send + 'static {
+        let base = state.config.read().await.base_url();
+
+        // (状态, 筛选条件, 对外域名, 上一批最后的 id)，None 表示已读完
+        let batches = stream::try_unfold(
+            (state, filter, base, Some(0)),
+            move |(state, filter, base, after_id)| async move {
+                let Some(after_id) = after_id else {
+                    return Ok(None);
+                };
+                let mut links = Link::find_links_after(
+                    &state.mysql_pool,
+                    &filter,
+                    after_id,
+                    EXPORT_BATCH_SIZE,
+                ).await.map_err(|(_, msg)| io::Error::other(msg))?;
+                if links.is_empty() {
+                    return Ok(None);
+                }
+                Tag::fill_link_tags(&state.mysql_pool, &mut links)
+                    .await
+                    .map_err(|(_, msg)| io::Error::other(msg))?;
+
+                let chunk = match format {
+                    ExportFormat::Csv => transfer::render_csv(&links, &base, after_id == 0),
+                    ExportFormat::Ndjson => transfer::render_ndjson(&links),
+                }.map_err(io::Error::other)?;
+                let next = if links.len() as u64 == EXPORT_BATCH_SIZE {
+                    links.last().map(|l| l.id)
+                } else {
+                    None
+                };
+
+                Ok(Some((Bytes::from(chunk), (state, filter, base, next))))
+            },
+        );
+
+        batches.inspect(|chunk| {
+            if let Err(e) = chunk {
+                warn!("export_links: 导出中断: error={}", e);
+            }
+        })
+    }
+
+    /// 导入短链：解析 CSV 后按批量创建的规则逐行校验并写入
+    /// 行数不超过 import_sync_max_rows 时直接返回报告，否则转为后台任务，返回任务编号
+    pub async fn import_links(
+        state: &Arc<AppState>,
+        body: &str,
+        user: &User,
+    ) -> Result<ImportReport, (StatusCode, String)> {
+        let rows = transfer::parse_import_csv(body).map_err(|msg| {
+            warn!("import_links: CSV 解析失败: user_id={}, error={}", user.id, msg);
+            (StatusCode::BAD_REQUEST, msg)
+        })?;
+
+        let (max_rows, sync_max_rows, job_ttl) = {
+            let config = state.config.read().await;
+            (config.import_max_rows, config.import_sync_max_rows, config.import_job_ttl)
+        };
+        if rows.is_empty() || rows.len() > max_rows {
+            warn!("import_links: 导入行数越界: user_id={}, rows={}, max={}", user.id, rows.len(), max_rows);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Import must contain between 1 and {} rows", max_rows),
+            ));
+        }
+
+        if rows.len() <= sync_max_rows {
+            let results = Self::import_rows(state, rows, user).await?;
+            return Ok(ImportReport::finished(None, results));
+        }
+
+        let job_id = Uuid::new_v4().to_string();
+        let report = ImportReport::pending(job_id.clone(), rows.len());
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("import_links: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
+        ImportJob::save(&mut conn, user.id, &job_id, &report, job_ttl).await?;
+
+        // 大批量导入耗时较长，在独立任务中执行，由 import_semaphore 限制同时进行的导入数
+        // 不占用后台 Redis 作业队列，避免与跳转统计争抢并发和连接
+        let state = state.clone();
+        let user = user.clone();
+        tokio::spawn(async move {
+            let _permit = match state.import_semaphore.acquire().await {
+                Ok(permit) => permit,
+                Err(e) => {
+                    warn!("import_links: import_semaphore closed: job_id={}, err={}", job_id, e);
+                    return;
+                }
+            };
+            info!("Importing links start: job_id={}", job_id);
+            Self::run_import_job(&state, &job_id, &user, rows).await;
+            info!("Importing links end: job_id={}", job_id);
+        });
+
+        Ok(report)
+    }
+
+    /// 后台执行导入任务，开始和结束时更新任务状态
+    async fn run_import_job(
+        state: &AppState,
+        job_id: &str,
+        user: &User,
+        rows: Vec<Result<ShortlinkCreateReq, String>>,
+    ) {
+        let mut report = ImportReport::pending(job_id.to_string(), rows.len());
+        report.status = ImportStatus::Running;
+        Self::save_import_job(state, user.id, job_id, &report).await;
+
+        let report = match Self::import_rows(state, rows, user).await {
+            Ok(results) => ImportReport::finished(Some(job_id.to_string()), results),
+            Err((_, msg)) => {
+                warn!("run_import_job: 导入失败: job_id={}, user_id={}, error={}", job_id, user.id, msg);
+                report.status = ImportStatus::Failed;
+                report.error = Some(msg);
+                report
+            },
+        };
+        Self::save_import_job(state, user.id, job_id, &report).await;
+    }
+
+    /// 保存导入任务状态，只在保存时取 Redis 连接，失败仅告警
+    async fn save_import_job(state: &AppState, user_id: u64, job_id: &str, report: &ImportReport) {
+        let job_ttl = state.config.read().await.import_job_ttl;
+        let mut conn = match state.redis_pool.get().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("save_import_job: Redis 获取连接失败: job_id={}, err={}", job_id, e);
+                return;
+            }
+        };
+        if let Err(e) = ImportJob::save(&mut conn, user_id, job_id, report, job_ttl).await {
+            warn!("save_import_job: 保存任务状态失败: job_id={}, error={:?}", job_id, e);
+        }
+    }
+
+    /// 逐行导入，结果按数据行下标排序
+    async fn import_rows(
+        state: &AppState,
+        rows: Vec<Result<ShortlinkCreateReq, String>>,
+        user: &User,
+    ) -> Result<Vec<BatchItemResult>, (StatusCode, String)> {
+        let mut results = Vec::with_capacity(rows.len());
+        // 可解析的行交给批量创建，记录其原始行下标
+        let mut row_indices = Vec::with_capacity(rows.len());
+        let mut items = Vec::with_capacity(rows.len());
+        for (index, row) in rows.into_iter().enumerate() {
+            match row {
+                Ok(item) => {
+                    row_indices.push(index);
+                    items.push(item);
+                },
+                Err(msg) => results.push(BatchItemResult::failed(index, BatchErrorCode::InvalidRow, msg)),
+            }
+        }
+
+        for mut result in Self::create_shortlinks_batch(state, &items, user).await? {
+            result.index = row_indices[result.index];
+            results.push(result);
+        }
+        results.sort_by_key(|r| r.index);
+
+        Ok(results)
+    }
+
+    /// 查询导入任务
+    pub async fn import_status(
+        state: &AppState,
+        user_id: u64,
+        job_id: &str,
+    ) -> Result<ImportReport, (StatusCode, String)> {
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("import_status: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
+
+        ImportJob::find(&mut conn, user_id, job_id).await?.ok_or_else(|| {
+            warn!("import_status: 导入任务不存在: user_id={}, job_id={}", user_id, job_id);
+            (StatusCode::NOT_FOUND, "Import job not found".into())
+        })
     }
 
     /// 删除短链
@@ -271,6 +1421,316 @@ impl ShortlinkService {
         Ok(())
     }
 
+    /// 编辑短链
+    /// 只能编辑自己名下的短链；提交后清理 shortlink:{code} 缓存，由下次访问回源重建
+    pub async fn update_link(
+        state: &AppState,
+        link_id: u64,
+        user_id: u64,
+        req: &LinkUpdateReq,
+    ) -> Result<(), (StatusCode, String)> {
+        if let Some(ttl) = req.ttl {
+            let (min_ttl, max_ttl) = {
+                let config = state.config.read().await;
+                (config.shortlink_min_ttl, config.shortlink_max_ttl)
+            };
+            Self::check_ttl(Some(ttl), min_ttl, max_ttl).inspect_err(|_| {
+                warn!("update_link: TTL越界: user_id={}, link_id={}, ttl={}", user_id, link_id, ttl);
+            })?;
+        }
+        if let Some(code) = req.short_code.as_deref() {
+            Self::check_custom_code(state, code, user_id)?;
+        }
+        Self::check_destinations(
+            state,
+            req.url.as_deref(),
+            req.targets.as_ref(),
+            None,
+            req.fallback_url.as_ref().and_then(Option::as_deref),
+            user_id,
+        )?;
+        let long_url_hash = req.url.as_deref().map(Self::url_hash);
+
+        let mut tx = state
+            .mysql_pool
+            .begin()
+            .await
+            .map_err(|e| {
+                warn!("update_link: DB Begin error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Begin error: {}", e))
+            })?;
+
+        let old_code = Link::find_owned_short_code(&mut tx, link_id, user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!("update_link: 短链不存在: user_id={}, link_id={}", user_id, link_id);
+                (StatusCode::NOT_FOUND, "Link not found".into())
+            })?;
+
+        // 短码未变化时不做迁移
+        let new_code = req.short_code.as_deref().filter(|c| *c != old_code);
+
+        let changes = LinkChanges {
+            long_url: req.url.as_deref().zip(long_url_hash.as_deref()),
+            ttl: req.ttl,
+            short_code: new_code,
+            targets: req.targets.as_ref().map(|t| Some(t).filter(|t| !t.is_empty())),
+            redirect_type: req.redirect_type,
+            interstitial: req.interstitial,
+            qr_public: req.qr_public,
+            title: req.title.as_ref().map(Option::as_deref),
+            note: req.note.as_ref().map(Option::as_deref),
+            fallback_url: req.fallback_url.as_ref().map(Option::as_deref),
+        };
+
+        Link::update_link(
+            &mut tx,
+            link_id,
+            user_id,
+            &changes,
+        ).await
+        .map_err(|e| match e {
+            (StatusCode::CONFLICT, _) => {
+                warn!("update_link: 短码已存在: user_id={}, short_code={:?}", user_id, new_code);
+                (StatusCode::BAD_REQUEST, "Short code already exists".into())
+            },
+            e => e,
+        })?;
+
+        if let Some(new_code) = new_code {
+            Link::rename_visit_logs(&mut tx, &old_code, new_code).await?;
+        }
+
+        if let Some(tags) = req.tags.as_deref() {
+            let tags = Self::normalize_tags(Some(tags));
+            Tag::set_link_tags(&mut tx, link_id, user_id, &tags).await?;
+        }
+
+        tx.commit().await.map_err(|e| {
+            warn!("update_link: DB Commit error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+        })?;
+
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("update_link: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
+        Link::evict_shortlink(&mut conn, &old_code, new_code).await?;
+
+        if let Some(url) = req.url.as_deref() {
+            if state.config.read().await.link_metadata_enabled {
+                Self::enqueue_metadata_fetch(state, new_code.unwrap_or(&old_code), url);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 恢复回收站中的短链，缓存由下次访问回源重建
+    pub async fn restore_links(
+        state: &AppState,
+        link_ids: Vec<u64>,
+        user_id: u64,
+    ) -> Result<(), (StatusCode, String)> {
+        let restored = Link::restore_links(
+            &state.mysql_pool,
+            &link_ids,
+            user_id,
+        ).await?;
+
+        if restored == 0 {
+            warn!("restore_links: 回收站中无可恢复的短链: user_id={}, ids={:?}", user_id, link_ids);
+        }
+
+        Ok(())
+    }
+
+    /// 获取回收站列表
+    pub async fn list_trash(
+        state: &AppState,
+        user_id: u64,
+        timezone: &str,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<LinkView>, i64), (StatusCode, String)> {
+        let (mut links, count) = Link::find_trash(
+            &state.mysql_pool,
+            user_id,
+            timezone,
+            limit,
+            offset,
+        ).await?;
+        Tag::fill_link_tags(&state.mysql_pool, &mut links).await?;
+        Ok((links, count))
+    }
+
+    /// 暂停 / 恢复短链
+    /// 暂停时清理 shortlink:{code} 缓存；恢复时按剩余有效期重新写入缓存
+    pub async fn set_link_status(
+        state: &AppState,
+        link_id: u64,
+        user_id: u64,
+        status: i8,
+    ) -> Result<(), (StatusCode, String)> {
+        let mut tx = state
+            .mysql_pool
+            .begin()
+            .await
+            .map_err(|e| {
+                warn!("set_link_status: DB Begin error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Begin error: {}", e))
+            })?;
+
+        let short_code = Link::find_owned_short_code(&mut tx, link_id, user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!("set_link_status: 短链不存在: user_id={}, link_id={}", user_id, link_id);
+                (StatusCode::NOT_FOUND, "Link not found".into())
+            })?;
+
+        Link::set_status(&mut tx, link_id, user_id, status).await?;
+
+        tx.commit().await.map_err(|e| {
+            warn!("set_link_status: DB Commit error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+        })?;
+
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("set_link_status: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
+
+        if status != LINK_STATUS_ACTIVE {
+            return Link::evict_shortlink(&mut conn, &short_code, None).await;
+        }
+
+        // 恢复：已生效且未过期则重新写入缓存
+        let target = Link::get_logn_url_from_mysql(&state.mysql_pool, &short_code).await?;
+        if target.activate_at.is_some_and(|t| t.and_utc() > Utc::now()) {
+            return Ok(());
+        }
+        let remaining = target.expire_at
+            .map(|expire| expire.and_utc().timestamp() - Utc::now().timestamp());
+        if remaining.is_some_and(|ttl| ttl <= 0) {
+            return Ok(());
+        }
+        let redis_max_ttl = state.config.read().await.redis_max_ttl;
+        Link::set_shortlink(
+            &mut conn,
+            &short_code,
+            &target.to_cached(),
+            Self::cache_ttl(remaining, redis_max_ttl),
+        ).await
+    }
+
+    /// 续期：在当前过期时间（已过期则为当前时间）基础上延长 ttl 秒，ttl 缺省为最小有效时间
+    /// 续期后的剩余有效时间不能超过最大有效时间；永久短链无需续期
+    pub async fn renew_link(
+        state: &AppState,
+        link_id: u64,
+        user_id: u64,
+        ttl: Option<i64>,
+    ) -> Result<DateTime<Utc>, (StatusCode, String)> {
+        let (min_ttl, max_ttl) = {
+            let config = state.config.read().await;
+            (config.shortlink_min_ttl, config.shortlink_max_ttl)
+        };
+        let ttl = Self::check_ttl(ttl, min_ttl, max_ttl).inspect_err(|_| {
+            warn!("renew_link: TTL越界: user_id={}, link_id={}, ttl={:?}", user_id, link_id, ttl);
+        })?;
+
+        let mut tx = state
+            .mysql_pool
+            .begin()
+            .await
+            .map_err(|e| {
+                warn!("renew_link: DB Begin error: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Begin error: {}", e))
+            })?;
+
+        let (short_code, expire_at) = Link::find_owned_expiry(&mut tx, link_id, user_id)
+            .await?
+            .ok_or_else(|| {
+                warn!("renew_link: 短链不存在: user_id={}, link_id={}", user_id, link_id);
+                (StatusCode::NOT_FOUND, "Link not found".into())
+            })?;
+        let Some(expire_at) = expire_at else {
+            warn!("renew_link: 永久短链无需续期: user_id={}, link_id={}", user_id, link_id);
+            return Err((StatusCode::BAD_REQUEST, "Link does not expire".into()));
+        };
+
+        let now = Utc::now().trunc_subsecs(0);
+        let new_expire_at = expire_at.and_utc().max(now) + chrono::Duration::seconds(ttl);
+        if (new_expire_at - now).num_seconds() > max_ttl {
+            warn!("renew_link: 续期后超过最大有效时间: user_id={}, link_id={}, ttl={}", user_id, link_id, ttl);
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Remaining lifetime after renewal must not exceed {} seconds", max_ttl),
+            ));
+        }
+
+        Link::renew(&mut tx, link_id, new_expire_at.naive_utc()).await?;
+
+        tx.commit().await.map_err(|e| {
+            warn!("renew_link: DB Commit error: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("DB Commit error: {}", e))
+        })?;
+
+        // 缓存 TTL 按旧的过期时间设置，清理后由下次访问回源重建
+        let mut conn = state.redis_pool.get().await.map_err(|e| {
+            warn!("renew_link: Redis 获取连接失败: err={}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("Redis err: {}", e))
+        })?;
+        Link::evict_shortlink(&mut conn, &short_code, None).await?;
+
+        Ok(new_expire_at)
+    }
+
+    /// 向即将过期短链的所有者发送提醒，返回发送成功的条数
+    /// 发送失败的不标记，下一轮重试
+    pub async fn send_expiry_reminders(
+        state: &AppState,
+        batch_size: u64,
+    ) -> Result<usize, (StatusCode, String)> {
+        let (hours, base) = {
+            let config = state.config.read().await;
+            (config.expiry_reminder_hours, config.base_url())
+        };
+
+        let mut sent = 0;
+        loop {
+            let links = Link::find_expiring_links(&state.mysql_pool, hours, batch_size).await?;
+            let fetched = links.len();
+
+            let mut reminded = Vec::with_capacity(fetched);
+            for link in links {
+                let reminder = ExpiryReminder {
+                    event: ExpiryReminder::EVENT,
+                    user_id: link.user_id,
+                    short_url: Self::short_url(&base, &link.short_code),
+                    email: link.email,
+                    link_id: link.id,
+                    short_code: link.short_code,
+                    long_url: link.long_url,
+                    expire_at: link.expire_at.and_utc(),
+                };
+                match state.notifier.notify(&reminder).await {
+                    Ok(()) => reminded.push(reminder.link_id),
+                    Err(e) => warn!("send_expiry_reminders: 发送失败: link_id={}, err={}", reminder.link_id, e),
+                }
+            }
+            Link::mark_reminded(&state.mysql_pool, &reminded).await?;
+            sent += reminded.len();
+
+            // 本批全部失败时停止，避免反复重试同一批
+            if (fetched as u64) < batch_size || reminded.is_empty() {
+                break;
+            }
+        }
+
+        Ok(sent)
+    }
+
     /// 点击量统计（按天）
     pub async fn get_link_stats(
         state: &AppState,
@@ -295,6 +1755,38 @@ impl ShortlinkService {
             days,
         ).await
     }
+
+    /// A/B 分流目标点击量统计
+    pub async fn get_variant_stats(
+        state: &AppState,
+        short_code: &str,
+        user_id: u64,
+        timezone: &str,
+        days: u8,
+    ) -> Result<Vec<VariantStats>, (StatusCode, String)> {
+        let max_days = state.config.read().await.max_stats_days;
+
+        if days > max_days {
+            warn!("get_variant_stats: Days exceeds maximum allowed: days={}, max_days={}, short_code={}, user_id={}", days, max_days, short_code, user_id);
+            return Err((StatusCode::BAD_REQUEST, "Days exceeds maximum allowed".into()));
+        }
+
+        Link::count_visits_by_variant(
+            &state.mysql_pool,
+            short_code,
+            timezone,
+            user_id,
+            days,
+        ).await
+    }
+
+    /// 按标签汇总短链数和点击量
+    pub async fn tag_stats(
+        state: &AppState,
+        user_id: u64,
+    ) -> Result<Vec<TagStats>, (StatusCode, String)> {
+        Tag::count_by_tag(&state.mysql_pool, user_id).await
+    }
     
 }
     
@@ -303,9 +1795,38 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_encode_base62() {
-        assert_eq!(ShortlinkService::encode_base62(1), "1");
-        assert_eq!(ShortlinkService::encode_base62(62), "10");
-        assert_eq!(ShortlinkService::encode_base62(62 * 62), "100");
+    fn test_normalize_url() {
+        assert_eq!(
+            ShortlinkService::normalize_url("HTTPS://Example.COM:443/a?b=1#top"),
+            "https://example.com/a?b=1"
+        );
+        assert_eq!(
+            ShortlinkService::url_hash("https://example.com"),
+            ShortlinkService::url_hash("https://EXAMPLE.com/#x")
+        );
+        assert_ne!(
+            ShortlinkService::url_hash("https://example.com/a"),
+            ShortlinkService::url_hash("https://example.com/A")
+        );
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![" spring ".to_string(), "Spring".to_string(), "email".to_string()];
+        assert_eq!(
+            ShortlinkService::normalize_tags(Some(&tags)),
+            vec!["spring".to_string(), "email".to_string()]
+        );
+        assert!(ShortlinkService::normalize_tags(None).is_empty());
+    }
+
+    #[test]
+    fn test_resolve_ttl() {
+        // 显式 ttl: null 与 permanent 一样需要永久短链权限
+        let err = ShortlinkService::resolve_ttl(Some(None), false, false, 60, 3600).unwrap_err();
+        assert_eq!(err.0, StatusCode::FORBIDDEN);
+        assert_eq!(ShortlinkService::resolve_ttl(Some(None), false, true, 60, 3600).unwrap(), None);
+        assert_eq!(ShortlinkService::resolve_ttl(None, false, false, 60, 3600).unwrap(), Some(60));
+        assert_eq!(ShortlinkService::resolve_ttl(Some(Some(120)), false, false, 60, 3600).unwrap(), Some(120));
     }
 }
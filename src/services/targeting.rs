@@ -0,0 +1,117 @@
+use password_hash::rand_core::{OsRng, RngCore};
+use crate::handlers::shortlink::{LinkTargets, LinkVariant};
+
+
+/// 访问者平台（按 User-Agent 粗略识别）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Ios,
+    Android,
+    Desktop,
+}
+
+
+/// 根据 User-Agent 识别平台，无法识别的一律按桌面端处理
+pub fn detect_platform(user_agent: &str) -> Platform {
+    if ["iPhone", "iPad", "iPod"].iter().any(|k| user_agent.contains(k)) {
+        return Platform::Ios;
+    }
+    // Windows Phone 的 UA 也会带 Android 字样
+    if user_agent.contains("Android") && !user_agent.contains("Windows Phone") {
+        return Platform::Android;
+    }
+    Platform::Desktop
+}
+
+
+impl LinkTargets {
+    /// 平台对应的目标 URL，未配置时返回 None，由调用方回退到默认长链
+    pub fn for_platform(&self, platform: Platform) -> Option<&str> {
+        match platform {
+            Platform::Ios => self.ios.as_deref(),
+            Platform::Android => self.android.as_deref(),
+            Platform::Desktop => self.desktop.as_deref(),
+        }
+    }
+
+    /// 是否未配置任何规则
+    pub fn is_empty(&self) -> bool {
+        self.ios.is_none() && self.android.is_none() && self.desktop.is_none()
+    }
+
+    /// 已配置的全部跳转地址
+    pub fn urls(&self) -> impl Iterator<Item = &str> {
+        [&self.ios, &self.android, &self.desktop]
+            .into_iter()
+            .filter_map(|url| url.as_deref())
+    }
+}
+
+
+/// 按权重选择分流目标，roll 为 [0, 总权重) 内的随机数
+fn pick_weighted(variants: &[LinkVariant], roll: u64) -> Option<usize> {
+    let mut acc = 0u64;
+    for (i, v) in variants.iter().enumerate() {
+        acc += v.weight as u64;
+        if roll < acc {
+            return Some(i);
+        }
+    }
+    None
+}
+
+
+/// 按权重随机选择分流目标，返回目标下标
+pub fn choose_variant(variants: &[LinkVariant]) -> Option<usize> {
+    let total: u64 = variants.iter().map(|v| v.weight as u64).sum();
+    if total == 0 {
+        return None;
+    }
+    // 总权重远小于 u64 范围，取模偏差可以忽略
+    pick_weighted(variants, OsRng.next_u64() % total)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IPHONE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+    const ANDROID: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Mobile Safari/537.36";
+    const WINDOWS: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+
+    #[test]
+    fn test_detect_platform() {
+        assert_eq!(detect_platform(IPHONE), Platform::Ios);
+        assert_eq!(detect_platform(ANDROID), Platform::Android);
+        assert_eq!(detect_platform(WINDOWS), Platform::Desktop);
+        assert_eq!(detect_platform(""), Platform::Desktop);
+    }
+
+    #[test]
+    fn test_for_platform() {
+        let targets = LinkTargets {
+            ios: Some("https://apps.apple.com/app/id1".into()),
+            android: None,
+            desktop: Some("https://www.example.com".into()),
+        };
+        assert_eq!(targets.for_platform(Platform::Ios), Some("https://apps.apple.com/app/id1"));
+        assert_eq!(targets.for_platform(Platform::Android), None);
+        assert!(!targets.is_empty());
+    }
+
+    #[test]
+    fn test_pick_weighted() {
+        let variants = vec![
+            LinkVariant { url: "https://a.example.com".into(), weight: 70 },
+            LinkVariant { url: "https://b.example.com".into(), weight: 30 },
+        ];
+        assert_eq!(pick_weighted(&variants, 0), Some(0));
+        assert_eq!(pick_weighted(&variants, 69), Some(0));
+        assert_eq!(pick_weighted(&variants, 70), Some(1));
+        assert_eq!(pick_weighted(&variants, 99), Some(1));
+        assert_eq!(pick_weighted(&variants, 100), None);
+        assert!(choose_variant(&variants).is_some());
+        assert_eq!(choose_variant(&[]), None);
+    }
+}
@@ -68,3 +68,69 @@ pub async fn spawn_expired_links_delete(state: Arc<AppState>) {
         }
     });
 }
+
+
+
+/// 到期提醒
+pub async fn spawn_expiry_reminders(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        // 从配置中读取到期提醒间隔
+        let t = state.config.read().await.bg_expiry_reminder_interval;
+        let mut ticker = interval(Duration::from_secs(t));
+        loop {
+            ticker.tick().await;
+            if !state.pending_set.insert(ScheduledJobKind::RemindExpiring) {
+                continue;
+            }
+
+            if let Err(e) = state.bg_redis_tx
+                .try_send(BackgroundJob::SpawnExpiryReminders) {
+                state.pending_set.remove(&ScheduledJobKind::RemindExpiring);
+                warn!("spawn_expiry_reminders: bg_redis_tx try_send failed: {e}");
+            }
+        }
+    });
+}
+
+
+/// 回收站清理
+pub async fn spawn_trash_purge(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        // 从配置中读取回收站清理间隔
+        let t = state.config.read().await.bg_trash_purge_interval;
+        let mut ticker = interval(Duration::from_secs(t));
+        loop {
+            ticker.tick().await;
+            if !state.pending_set.insert(ScheduledJobKind::PurgeTrash) {
+                continue;
+            }
+
+            if let Err(e) = state.bg_redis_tx
+                .try_send(BackgroundJob::SpawnTrashPurge) {
+                state.pending_set.remove(&ScheduledJobKind::PurgeTrash);
+                warn!("spawn_trash_purge: bg_redis_tx try_send failed: {e}");
+            }
+        }
+    });
+}
+
+
+/// 目标地址域名黑名单重新加载，读取失败时保留原黑名单
+pub async fn spawn_url_blocklist_reload(state: Arc<AppState>) {
+    tokio::spawn(async move {
+        // 从配置中读取重新加载间隔
+        let t = state.config.read().await.url_blocklist_reload_interval;
+        let mut ticker = interval(Duration::from_secs(t));
+        // 启动时已加载，跳过立即触发的第一次
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let s = state.clone();
+            match tokio::task::spawn_blocking(move || s.url_policy.reload_blocklist()).await {
+                Ok(Ok(_)) => {},
+                Ok(Err(e)) => warn!("spawn_url_blocklist_reload: reload failed: {e}"),
+                Err(e) => warn!("spawn_url_blocklist_reload: spawn_blocking failed: {e}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,222 @@
+use csv::{ReaderBuilder, Trim, Writer};
+use serde::Deserialize;
+
+use crate::{
+    handlers::shortlink::ShortlinkCreateReq,
+    models::link::LinkView,
+    services::shortlink::ShortlinkService,
+};
+
+
+/// 导出 CSV 的列
+const CSV_COLUMNS: [&str; 12] = [
+    "id",
+    "short_code",
+    "short_url",
+    "long_url",
+    "title",
+    "note",
+    "tags",
+    "click_count",
+    "max_clicks",
+    "status",
+    "created_at",
+    "expire_at",
+];
+
+
+/// 导入 CSV 的一行，列顺序以表头为准，除 long_url 外均可省略或留空
+#[derive(Debug, Deserialize)]
+struct ImportRow {
+    long_url: String,
+    #[serde(default)]
+    short_code: Option<String>,
+    #[serde(default)]
+    ttl: Option<i64>,
+    #[serde(default)]
+    title: Option<String>,
+}
+
+impl From<ImportRow> for ShortlinkCreateReq {
+    fn from(row: ImportRow) -> Self {
+        ShortlinkCreateReq {
+            url: row.long_url,
+            ttl: row.ttl.map(Some),
+            short_code: row.short_code,
+            title: row.title,
+            ..Default::default()
+        }
+    }
+}
+
+
+/// 解析导入的 CSV，返回每个数据行的创建请求或解析错误
+/// 表头缺少 long_url 列时整体报错
+pub fn parse_import_csv(body: &str) -> Result<Vec<Result<ShortlinkCreateReq, String>>, String> {
+    let mut reader = ReaderBuilder::new()
+        .trim(Trim::All)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV header: {}", e))?
+        .clone();
+    if !headers.iter().any(|h| h == "long_url") {
+        return Err("CSV header must contain a long_url column".into());
+    }
+
+    Ok(reader
+        .deserialize::<ImportRow>()
+        .map(|row| row.map(ShortlinkCreateReq::from).map_err(|e| format!("Invalid row: {}", e)))
+        .collect())
+}
+
+/// 以 = + - @ 开头的单元格会被表格软件当作公式执行，前面补一个单引号按文本显示
+fn escape_formula(value: &str) -> String {
+    if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// 导出为 CSV，header 为 true 时先写表头
+/// 用户可控的文本列（目标地址、标题、备注、标签）做公式转义
+pub fn render_csv(links: &[LinkView], base: &str, header: bool) -> Result<Vec<u8>, String> {
+    let mut writer = Writer::from_writer(Vec::new());
+    if header {
+        writer.write_record(CSV_COLUMNS).map_err(|e| format!("CSV write error: {}", e))?;
+    }
+    for link in links {
+        writer.write_record([
+            link.id.to_string(),
+            link.short_code.clone(),
+            ShortlinkService::short_url(base, &link.short_code),
+            escape_formula(&link.long_url),
+            escape_formula(link.title.as_deref().unwrap_or_default()),
+            escape_formula(link.note.as_deref().unwrap_or_default()),
+            escape_formula(&link.tags.join(";")),
+            link.click_count.to_string(),
+            link.max_clicks.map(|m| m.to_string()).unwrap_or_default(),
+            link.status.to_string(),
+            link.created_at.clone(),
+            link.expire_at.clone().unwrap_or_default(),
+        ])
+        .map_err(|e| format!("CSV write error: {}", e))?;
+    }
+    writer.into_inner().map_err(|e| format!("CSV flush error: {}", e))
+}
+
+/// 导出为 NDJSON，每行一条短链
+pub fn render_ndjson(links: &[LinkView]) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    for link in links {
+        serde_json::to_writer(&mut out, link).map_err(|e| format!("serialize error: {}", e))?;
+        out.push(b'\n');
+    }
+    Ok(out)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(id: u64, short_code: &str, title: Option<&str>, tags: &[&str]) -> LinkView {
+        LinkView {
+            id,
+            user_id: 1,
+            short_code: short_code.into(),
+            long_url: "https://www.example.com/a,b".into(),
+            click_count: 3,
+            max_clicks: None,
+            status: 1,
+            activate_at: None,
+            expire_at: Some("2026-10-16 08:00:00".into()),
+            created_at: "2026-10-15 08:00:00".into(),
+            deleted_at: None,
+            targets: None,
+            variants: None,
+            forward_query: false,
+            utm: None,
+            redirect_type: None,
+            interstitial: false,
+            qr_public: false,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            title: title.map(String::from),
+            note: None,
+            meta_title: None,
+            meta_description: None,
+            meta_favicon: None,
+            fallback_url: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_import_csv() {
+        // 列顺序以表头为准，空值按缺省处理，无法解析的行单独报错
+        let body = "title,long_url,ttl,short_code\n\
+                    Launch,https://www.example.com/launch,3600,launch1\n\
+                    ,https://www.example.com/plain,,\n\
+                    Bad ttl,https://www.example.com/bad,soon,\n";
+        let rows = parse_import_csv(body).unwrap();
+        assert_eq!(rows.len(), 3);
+
+        let first = rows[0].as_ref().unwrap();
+        assert_eq!(first.url, "https://www.example.com/launch");
+        assert_eq!(first.ttl, Some(Some(3600)));
+        assert_eq!(first.short_code.as_deref(), Some("launch1"));
+        assert_eq!(first.title.as_deref(), Some("Launch"));
+
+        let second = rows[1].as_ref().unwrap();
+        assert_eq!(second.ttl, None);
+        assert_eq!(second.short_code, None);
+        assert_eq!(second.title, None);
+
+        assert!(rows[2].is_err());
+
+        // 只有 long_url 一列
+        let rows = parse_import_csv("long_url\nhttps://www.example.com\n").unwrap();
+        assert_eq!(rows[0].as_ref().unwrap().url, "https://www.example.com");
+
+        // 缺少 long_url 列
+        assert!(parse_import_csv("url,title\nhttps://www.example.com,x\n").is_err());
+    }
+
+    #[test]
+    fn test_render() {
+        let links = [
+            link(1, "abc", Some("Launch"), &["a", "b"]),
+            link(2, "def", None, &[]),
+        ];
+
+        let csv = String::from_utf8(render_csv(&links, "http://127.0.0.1:3000/", true).unwrap()).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("id,short_code,short_url,long_url,"));
+        assert_eq!(
+            lines[1],
+            "1,abc,http://127.0.0.1:3000/s/abc,\"https://www.example.com/a,b\",Launch,,a;b,3,,1,2026-10-15 08:00:00,2026-10-16 08:00:00",
+        );
+
+        // 公式开头的单元格加单引号
+        let mut formula = link(3, "ghi", Some("=HYPERLINK(\"https://evil.example\")"), &["-1", "ok"]);
+        formula.note = Some("@SUM(A1)".into());
+        let csv = String::from_utf8(render_csv(&[formula], "http://127.0.0.1:3000", false).unwrap()).unwrap();
+        assert!(csv.contains("\"'=HYPERLINK(\"\"https://evil.example\"\")\",'@SUM(A1),'-1;ok,"));
+
+        // 后续批次不重复表头
+        let csv = String::from_utf8(render_csv(&links[1..], "http://127.0.0.1:3000", false).unwrap()).unwrap();
+        assert!(csv.starts_with("2,def,"));
+
+        let ndjson = String::from_utf8(render_ndjson(&links).unwrap()).unwrap();
+        let rows: Vec<serde_json::Value> = ndjson
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["tags"], serde_json::json!(["a", "b"]));
+        assert_eq!(rows[1]["short_code"], "def");
+    }
+}
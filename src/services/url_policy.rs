@@ -0,0 +1,237 @@
+use std::{
+    collections::HashSet,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    sync::RwLock,
+};
+use url::{Host, Url};
+
+use crate::config::AppConfig;
+
+
+/// 目标地址安全策略：协议白名单、域名黑名单、内网地址与回环跳转检测
+pub struct UrlPolicy {
+    /// 允许的协议（小写）
+    allowed_schemes: HashSet<String>,
+    /// 本服务的域名（小写），目标指向这些域名会形成循环跳转
+    own_hosts: HashSet<String>,
+    /// 域名黑名单文件，None 表示不启用
+    blocklist_file: Option<String>,
+    /// 黑名单域名（小写），同时匹配其子域名；可定时重新加载
+    blocklist: RwLock<HashSet<String>>,
+}
+
+impl UrlPolicy {
+    /// 根据配置构建，黑名单文件每行一个域名，忽略空行和 # 注释
+    pub fn from_config(cfg: &AppConfig) -> Result<Self, String> {
+        let allowed_schemes: HashSet<String> = cfg.url_allowed_schemes
+            .split(',')
+            .map(|s| s.trim().to_ascii_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect();
+        if allowed_schemes.is_empty() {
+            return Err("URL_ALLOWED_SCHEMES must contain at least one scheme".into());
+        }
+
+        // 监听地址和对外基础地址的主机部分，加上对外域名
+        let listen_host = cfg.addr
+            .rsplit_once(':')
+            .map_or(cfg.addr.as_str(), |(host, _)| host);
+        let base_url = Url::parse(&cfg.base_url()).ok();
+        let base_host = base_url.as_ref().and_then(Url::host_str);
+        let own_hosts = std::iter::once(listen_host)
+            .chain(base_host)
+            .chain(cfg.shortlink_hosts.as_deref().unwrap_or_default().split(','))
+            .map(normalize_host)
+            .filter(|h| !h.is_empty())
+            .collect();
+
+        let blocklist_file = cfg.url_blocklist_file.clone().filter(|p| !p.is_empty());
+        let blocklist = match blocklist_file.as_deref() {
+            Some(path) => load_blocklist(path)?,
+            None => HashSet::new(),
+        };
+
+        Ok(Self {
+            allowed_schemes,
+            own_hosts,
+            blocklist_file,
+            blocklist: RwLock::new(blocklist),
+        })
+    }
+
+    /// 重新加载黑名单文件，返回加载的域名数；读取失败时保留原黑名单
+    pub fn reload_blocklist(&self) -> Result<usize, String> {
+        let Some(path) = self.blocklist_file.as_deref() else {
+            return Ok(0);
+        };
+        let blocklist = load_blocklist(path)?;
+        let count = blocklist.len();
+        *self.blocklist.write().unwrap_or_else(|e| e.into_inner()) = blocklist;
+        Ok(count)
+    }
+
+    /// 校验目标地址，失败时返回可直接展示给客户端的原因
+    pub fn check(&self, raw: &str) -> Result<(), String> {
+        let url = Url::parse(raw).map_err(|_| "Invalid URL".to_string())?;
+
+        if !self.allowed_schemes.contains(url.scheme()) {
+            return Err(format!("URL scheme '{}' is not allowed", url.scheme()));
+        }
+
+        let host = match url.host() {
+            Some(Host::Domain(domain)) => normalize_host(domain),
+            Some(Host::Ipv4(ip)) => {
+                if is_internal_v4(ip) {
+                    return Err("URL must not point to a private or loopback address".into());
+                }
+                ip.to_string()
+            },
+            Some(Host::Ipv6(ip)) => {
+                if is_internal_v6(ip) {
+                    return Err("URL must not point to a private or loopback address".into());
+                }
+                format!("[{}]", ip)
+            },
+            None => return Err("URL must have a host".into()),
+        };
+
+        if host == "localhost" || host.ends_with(".localhost") {
+            return Err("URL must not point to a private or loopback address".into());
+        }
+
+        if self.own_hosts.contains(&host) {
+            return Err("URL must not point back to this service".into());
+        }
+
+        let blocklist = self.blocklist.read().unwrap_or_else(|e| e.into_inner());
+        if parent_domains(&host).any(|d| blocklist.contains(d)) {
+            return Err("URL domain is blocked".into());
+        }
+
+        Ok(())
+    }
+}
+
+
+/// 域名小写并去掉末尾的点
+fn normalize_host(host: &str) -> String {
+    host.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+/// 域名自身及其各级父域名：a.b.com → a.b.com, b.com, com
+fn parent_domains(host: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(host), |h| h.split_once('.').map(|(_, rest)| rest))
+}
+
+/// 读取黑名单文件，允许 `*.example.com` / `.example.com` 写法
+fn load_blocklist(path: &str) -> Result<HashSet<String>, String> {
+    Ok(fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read URL_BLOCKLIST_FILE {}: {}", path, e))?
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| normalize_host(line.trim_start_matches("*.").trim_start_matches('.')))
+        .collect())
+}
+
+/// 内网、回环、链路本地等不可对外的地址，供抓取目标页时过滤 DNS 解析结果
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_internal_v4(ip),
+        IpAddr::V6(ip) => is_internal_v6(ip),
+    }
+}
+
+/// 内网、回环、链路本地等不可对外的 IPv4 地址
+fn is_internal_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        // 100.64.0.0/10 运营商级 NAT
+        || (a == 100 && (b & 0xc0) == 64)
+}
+
+/// 内网、回环、链路本地等不可对外的 IPv6 地址，IPv4 映射地址按 IPv4 判断
+fn is_internal_v6(ip: Ipv6Addr) -> bool {
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_internal_v4(v4);
+    }
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        // fc00::/7 唯一本地地址
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10 链路本地地址
+        || (first & 0xffc0) == 0xfe80
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(blocklist: &[&str]) -> UrlPolicy {
+        UrlPolicy {
+            allowed_schemes: ["http", "https"].into_iter().map(String::from).collect(),
+            own_hosts: ["sho.rt", "127.0.0.1"].into_iter().map(String::from).collect(),
+            blocklist_file: None,
+            blocklist: RwLock::new(blocklist.iter().map(|d| d.to_string()).collect()),
+        }
+    }
+
+    #[test]
+    fn test_scheme_allowlist() {
+        let p = policy(&[]);
+        assert!(p.check("https://www.example.com/a").is_ok());
+        assert!(p.check("http://www.example.com").is_ok());
+        assert!(p.check("javascript:alert(1)").is_err());
+        assert!(p.check("data:text/html,<script>alert(1)</script>").is_err());
+        assert!(p.check("file:///etc/passwd").is_err());
+        assert!(p.check("ftp://www.example.com").is_err());
+    }
+
+    #[test]
+    fn test_internal_addresses() {
+        let p = policy(&[]);
+        for url in [
+            "http://127.0.0.1:8080/",
+            "http://10.0.0.1/",
+            "http://192.168.1.1/",
+            "http://172.16.0.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://0x7f000001/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://localhost/",
+            "http://app.localhost/",
+        ] {
+            assert!(p.check(url).is_err(), "{}", url);
+        }
+        assert!(p.check("http://8.8.8.8/").is_ok());
+        assert!(p.check("http://[2001:4860:4860::8888]/").is_ok());
+    }
+
+    #[test]
+    fn test_loop_detection() {
+        let p = policy(&[]);
+        assert!(p.check("https://sho.rt/s/abc").is_err());
+        assert!(p.check("https://SHO.RT./s/abc").is_err());
+        assert!(p.check("https://www.sho.rt/s/abc").is_ok());
+    }
+
+    #[test]
+    fn test_blocklist_matches_subdomains() {
+        let p = policy(&["evil.com"]);
+        assert!(p.check("https://evil.com/").is_err());
+        assert!(p.check("https://a.b.evil.com/").is_err());
+        assert!(p.check("https://notevil.com/").is_ok());
+    }
+}
@@ -189,4 +189,20 @@ impl UserService {
             nickname: user.nickname,
         })
     }
+
+    /// 设置账号级过期跳转地址，需符合目标地址策略
+    pub async fn set_fallback_url(
+        state: &AppState,
+        user_id: u64,
+        fallback_url: Option<&str>,
+    ) -> Result<(), (StatusCode, String)> {
+        if let Some(url) = fallback_url {
+            state.url_policy.check(url).map_err(|msg| {
+                warn!("set_fallback_url: 目标地址不允许: user_id={}, url={}, reason={}", user_id, url, msg);
+                (StatusCode::BAD_REQUEST, msg)
+            })?;
+        }
+
+        User::set_fallback_url(&state.mysql_pool, user_id, fallback_url).await
+    }
 }
\ No newline at end of file
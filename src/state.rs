@@ -1,8 +1,13 @@
+use std::sync::Arc;
 use sqlx::MySqlPool;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 use deadpool_redis::Pool;
 use crate::config::AppConfig;
-use crate::services::background_jobs::BackgroundJob;
+use crate::handlers::pages::ErrorPages;
+use crate::services::background_jobs::{BackgroundJob, MetadataJob};
+use crate::services::notifier::Notifier;
+use crate::services::short_code::{CustomCodePolicy, ShortCodeGenerator};
+use crate::services::url_policy::UrlPolicy;
 use tokio::sync::mpsc::Sender;
 use dashmap::DashSet;
 
@@ -11,7 +16,9 @@ use dashmap::DashSet;
 pub enum ScheduledJobKind {
     SyncClick, 
     SyncVisitLog, 
-    DeleteExpired
+    DeleteExpired,
+    RemindExpiring,
+    PurgeTrash,
 }
 
 
@@ -21,4 +28,17 @@ pub struct AppState {
     pub bg_redis_tx: Sender<BackgroundJob>,
     pub config: RwLock<AppConfig>,
     pub pending_set: DashSet<ScheduledJobKind>,
+    pub code_generator: Box<dyn ShortCodeGenerator>,
+    pub code_policy: CustomCodePolicy,
+    pub url_policy: Arc<UrlPolicy>,
+    /// 抓取目标页元数据的 HTTP 客户端
+    pub metadata_client: reqwest::Client,
+    /// 目标页元数据抓取作业队列
+    pub metadata_tx: Sender<MetadataJob>,
+    /// 到期提醒发送方式
+    pub notifier: Box<dyn Notifier>,
+    /// 不存在 / 过期 / 暂停的错误页
+    pub error_pages: ErrorPages,
+    /// 限制同时执行的后台导入任务数
+    pub import_semaphore: Semaphore,
 }
\ No newline at end of file
@@ -0,0 +1,72 @@
+use std::env;
+use chrono::{Duration, NaiveDateTime, Utc};
+use reqwest::{Client, StatusCode, redirect::Policy};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::LinkList;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_link_not_yet_active() {
+    // 生效前访问和预览都返回 403，过期时间从生效时间起算
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let activate_at = Utc::now() + Duration::hours(1);
+    let shorten_body = json!({
+        "url": "https://www.example.com/launch",
+        "short_code": "launch1",
+        "ttl": 3600,
+        "activate_at": activate_at.to_rfc3339(),
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    // 连续访问都不应被缓存放行
+    for _ in 0..2 {
+        let res = client
+            .get(format!("http://{}/s/launch1", addr))
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    // 预览页同样不展示未生效的短链
+    for url in [
+        format!("http://{}/p/launch1", addr),
+        format!("http://{}/s/launch1+", addr),
+    ] {
+        let res = client
+            .get(&url)
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+
+    let links = client
+        .get(format!("http://{}/links?short_code=launch1", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link = &links.links[0];
+    let fmt = "%Y-%m-%d %H:%M:%S";
+    let activate = NaiveDateTime::parse_from_str(link.activate_at.as_deref().unwrap(), fmt).unwrap();
+    let expire = NaiveDateTime::parse_from_str(link.expire_at.as_deref().unwrap(), fmt).unwrap();
+    assert!((expire - activate - Duration::seconds(3600)).num_seconds().abs() <= 1);
+}
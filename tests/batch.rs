@@ -0,0 +1,62 @@
+use std::env;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::{BatchCreateResp, BatchErrorCode};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_create_batch() {
+    // 批量创建：每条独立返回结果
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let batch_url = format!("http://{}/shorten/batch", addr);
+    let shortlink_max_ttl = env::var("SHORTLINK_MAX_TTL")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(3600);
+
+    // 获取 token
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let batch_body = json!([
+        { "url": "https://www.example.com/batch0" },
+        { "url": "not a url" },
+        { "url": "https://www.example.com/batch2", "ttl": shortlink_max_ttl + 1 },
+        { "url": "https://www.example.com/batch3", "short_code": "batch_dup" },
+        { "url": "https://www.example.com/batch4", "short_code": "batch_dup" },
+    ]);
+
+    let res = client
+        .post(&batch_url)
+        .bearer_auth(&token)
+        .json(&batch_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let resp = res.json::<BatchCreateResp>().await.unwrap();
+    assert_eq!(resp.results.len(), 5);
+
+    assert!(resp.results[0].short_url.is_some());
+    assert_eq!(resp.results[1].error.as_ref().unwrap().code, BatchErrorCode::InvalidUrl);
+    assert_eq!(resp.results[2].error.as_ref().unwrap().code, BatchErrorCode::TtlOutOfRange);
+    assert!(resp.results[3].short_url.is_some());
+    assert_eq!(resp.results[4].error.as_ref().unwrap().code, BatchErrorCode::CodeTaken);
+
+    // 空数组
+    let res = client
+        .post(&batch_url)
+        .bearer_auth(&token)
+        .json(&json!([]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
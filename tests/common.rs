@@ -21,7 +21,7 @@ fn init() {
             .await
             .expect("connect to db");
         
-        for i in 0..4 {
+        for i in 0..7 {
             let salt = SaltString::generate(&mut OsRng);
             let password = format!("password{}", i);
             let argon2 = Argon2::default();
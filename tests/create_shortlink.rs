@@ -2,6 +2,7 @@ use reqwest::{Client, StatusCode};
 use serde_json;
 use std::env;
 use tokio_shortlink::services::LoginResp;
+use tokio_shortlink::handlers::shortlink::{LinkList, ShortlinkCreateResp};
 
 mod common;
 
@@ -176,3 +177,202 @@ async fn test_create_shortlink_invalid_short_code() {
         .unwrap();
     assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 }
+
+
+#[tokio::test]
+async fn test_create_shortlink_dedupe() {
+    // 开启 dedupe 时，同一用户相同 URL 返回已有短链
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let create_url = format!("http://{}/shorten", addr);
+    let login_url = format!("http://{}/login", addr);
+
+    // 登录获取 token
+    let login_body = serde_json::json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let create_body = serde_json::json!({
+        "url": "https://www.example.com/dedupe",
+        "dedupe": true
+    });
+    // 规范化后与上一个 URL 相同
+    let create_body2 = serde_json::json!({
+        "url": "HTTPS://WWW.EXAMPLE.COM:443/dedupe#section",
+        "dedupe": true
+    });
+
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&create_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let first = res.json::<ShortlinkCreateResp>().await.unwrap();
+
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&create_body2)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let second = res.json::<ShortlinkCreateResp>().await.unwrap();
+    assert_eq!(first.short_url, second.short_url);
+
+    // 显式指定有效期时不复用，避免忽略请求的 ttl
+    let shortlink_min_ttl = env::var("SHORTLINK_MIN_TTL")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(60);
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "url": "https://www.example.com/dedupe",
+            "ttl": shortlink_min_ttl,
+            "dedupe": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let third = res.json::<ShortlinkCreateResp>().await.unwrap();
+    assert_ne!(first.short_url, third.short_url);
+
+    // 并发的相同请求只创建一条
+    let race_body = serde_json::json!({
+        "url": "https://www.example.com/dedupe-race",
+        "dedupe": true
+    });
+    let send = || client.post(&create_url).bearer_auth(&token).json(&race_body).send();
+    let (a, b) = tokio::join!(send(), send());
+    let (a, b) = (a.unwrap(), b.unwrap());
+    assert_eq!(a.status(), StatusCode::OK);
+    assert_eq!(b.status(), StatusCode::OK);
+    assert_eq!(
+        a.json::<ShortlinkCreateResp>().await.unwrap().short_url,
+        b.json::<ShortlinkCreateResp>().await.unwrap().short_url,
+    );
+
+    // 去重短链被编辑（如加标题）后不再复用
+    let edited_body = serde_json::json!({
+        "url": "https://www.example.com/dedupe-edited",
+        "dedupe": true
+    });
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&edited_body)
+        .send()
+        .await
+        .unwrap();
+    let edited = res.json::<ShortlinkCreateResp>().await.unwrap();
+    let short_code = edited.short_url.rsplit('/').next().unwrap();
+    let links = client
+        .get(format!("http://{}/links", addr))
+        .bearer_auth(&token)
+        .query(&[("short_code", short_code)])
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link_id = links.links.iter().find(|l| l.short_code == short_code).unwrap().id;
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&serde_json::json!({ "title": "Edited" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&edited_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_ne!(res.json::<ShortlinkCreateResp>().await.unwrap().short_url, edited.short_url);
+}
+
+
+#[tokio::test]
+async fn test_create_shortlink_custom_code_policy() {
+    // 自定义短码不符合规则返回 400
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let create_url = format!("http://{}/shorten", addr);
+    let login_url = format!("http://{}/login", addr);
+
+    let login_body = serde_json::json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let long_code = "a".repeat(40);
+    for short_code in [long_code.as_str(), "a/b", "has space", "admin"] {
+        let create_body = serde_json::json!({
+            "url": "https://www.example.com",
+            "short_code": short_code
+        });
+        let res = client
+            .post(&create_url)
+            .bearer_auth(&token)
+            .json(&create_body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "short_code={}", short_code);
+    }
+}
+
+
+#[tokio::test]
+async fn test_create_permanent_shortlink_forbidden() {
+    // 无权限账号创建永久短链返回 403，显式 ttl: null 同样视为永久
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let create_url = format!("http://{}/shorten", addr);
+    let login_url = format!("http://{}/login", addr);
+
+    let login_body = serde_json::json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "url": "https://www.example.com/permanent",
+            "permanent": true
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = client
+        .post(&create_url)
+        .bearer_auth(&token)
+        .json(&serde_json::json!({
+            "url": "https://www.example.com/permanent",
+            "ttl": null
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+}
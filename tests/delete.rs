@@ -38,7 +38,7 @@ async fn test_delete_success() {
         .unwrap();
     let links = res.json::<LinkList>().await.unwrap();
     assert_eq!(links.links.len(), 1);
-    assert_eq!(links.count, 1);
+    assert_eq!(links.count, Some(1));
 
     let link_id = links.links[0].id;
     
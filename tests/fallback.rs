@@ -0,0 +1,196 @@
+use std::env;
+use reqwest::{Client, StatusCode, header, redirect::Policy};
+use serde_json::json;
+use serial_test::serial;
+use sqlx::MySqlPool;
+use tokio_shortlink::{handlers::shortlink::ErrorResp, models::link::Link};
+
+mod common;
+
+
+/// 直接写入一条已过期的短链，避免创建时写入缓存
+async fn insert_expired_link(short_code: &str, email: &str, fallback_url: Option<&str>) {
+    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL not found");
+    let pool = MySqlPool::connect(&db_url).await.expect("connect to db");
+    sqlx::query!(
+        r#"INSERT IGNORE INTO links (user_id, short_code, long_url, expire_at, fallback_url)
+           SELECT id, ?, 'https://www.example.com/expired', NOW() - INTERVAL 1 HOUR, ?
+           FROM users WHERE email = ?"#,
+        short_code,
+        fallback_url,
+        email,
+    )
+    .execute(&pool)
+    .await
+    .expect("insert expired link");
+}
+
+
+#[tokio::test]
+#[serial]
+async fn test_expired_link_fallback() {
+    // 已过期：短链级过期跳转地址优先，其次账号级
+    // 专用账号，账号级过期跳转地址不影响其它用例；与删除任务用例串行，避免 fallback2 被提前删除
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+
+    insert_expired_link("fallback1", "test5@example.com", Some("https://www.example.com/link-fallback")).await;
+    insert_expired_link("fallback2", "test5@example.com", None).await;
+
+    let res = client
+        .get(format!("http://{}/s/fallback1", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert_eq!(res.headers()["location"], "https://www.example.com/link-fallback");
+
+    // 未设置过期跳转地址时返回过期页
+    let res = client
+        .get(format!("http://{}/s/fallback2", addr))
+        .header("User-Agent", "test")
+        .header(header::ACCEPT, "text/html")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // 设置账号级过期跳转地址
+    let login_body = json!({
+        "email": "test5@example.com",
+        "password": "password5",
+    });
+    let token = common::login(&login_url, &login_body).await;
+    let fallback_url = format!("http://{}/account/fallback", addr);
+    let res = client
+        .put(&fallback_url)
+        .bearer_auth(&token)
+        .json(&json!({ "fallback_url": "https://www.example.com/account-fallback" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("http://{}/s/fallback2", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert_eq!(res.headers()["location"], "https://www.example.com/account-fallback");
+
+    let res = client
+        .get(format!("http://{}/s/fallback1", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["location"], "https://www.example.com/link-fallback");
+
+    // 地址不合法
+    let res = client
+        .put(&fallback_url)
+        .bearer_auth(&token)
+        .json(&json!({ "fallback_url": "not a url" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 清除账号级过期跳转地址
+    let res = client
+        .put(&fallback_url)
+        .bearer_auth(&token)
+        .json(&json!({ "fallback_url": null }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+
+#[tokio::test]
+#[serial]
+async fn test_fallback_survives_expired_purge() {
+    // 过期短链删除任务在保留期内保留设置了过期跳转地址的短链，超过保留期或未设置的照常删除
+    // 回收站中的短链留给回收站清理任务
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+
+    insert_expired_link("fallback3", "test6@example.com", Some("https://www.example.com/kept-fallback")).await;
+    insert_expired_link("fallback4", "test6@example.com", None).await;
+
+    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL not found");
+    let pool = MySqlPool::connect(&db_url).await.expect("connect to db");
+    sqlx::query!(
+        r#"INSERT IGNORE INTO links (user_id, short_code, long_url, expire_at, fallback_url)
+           SELECT id, 'fallback5', 'https://www.example.com/expired', NOW() - INTERVAL 31 DAY,
+                  'https://www.example.com/old-fallback'
+           FROM users WHERE email = 'test6@example.com'"#,
+    )
+    .execute(&pool)
+    .await
+    .expect("insert long expired link");
+    sqlx::query!(
+        r#"INSERT IGNORE INTO links (user_id, short_code, long_url, expire_at, deleted_at)
+           SELECT id, 'fallback6', 'https://www.example.com/expired', NOW() - INTERVAL 1 HOUR, NOW()
+           FROM users WHERE email = 'test6@example.com'"#,
+    )
+    .execute(&pool)
+    .await
+    .expect("insert trashed link");
+
+    Link::delete_expired_links(&pool, 30).await.unwrap();
+
+    let res = client
+        .get(format!("http://{}/s/fallback3", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FOUND);
+    assert_eq!(res.headers()["location"], "https://www.example.com/kept-fallback");
+
+    let kept = sqlx::query_scalar!(
+        r#"SELECT short_code FROM links
+           WHERE short_code IN ('fallback3', 'fallback4', 'fallback5', 'fallback6')
+           ORDER BY short_code"#
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(kept, ["fallback3", "fallback6"]);
+}
+
+
+#[tokio::test]
+async fn test_not_found_negotiation() {
+    // 不存在的短码：浏览器返回 HTML 错误页，API 客户端返回 JSON
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let redirect_url = format!("http://{}/s/nosuchcode", addr);
+
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .header(header::ACCEPT, "text/html,application/xhtml+xml,*/*;q=0.8")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(res.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+    assert!(res.text().await.unwrap().contains("Link not found"));
+
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .header(header::ACCEPT, "application/json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert!(!res.json::<ErrorResp>().await.unwrap().error.is_empty());
+}
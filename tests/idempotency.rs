@@ -0,0 +1,66 @@
+use std::env;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::ShortlinkCreateResp;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_create_shortlink_idempotent() {
+    // 同一 Idempotency-Key 重试返回首次结果，请求体不同返回 422
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    // 获取 token
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let idempotency_key = uuid::Uuid::new_v4().to_string();
+    let create_body = json!({
+        "url": "https://www.example.com/idempotent",
+    });
+
+    // 首次请求
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .header("Idempotency-Key", &idempotency_key)
+        .json(&create_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let first = res.json::<ShortlinkCreateResp>().await.unwrap();
+
+    // 重试：返回相同短链
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .header("Idempotency-Key", &idempotency_key)
+        .json(&create_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let second = res.json::<ShortlinkCreateResp>().await.unwrap();
+    assert_eq!(first.short_url, second.short_url);
+
+    // 相同幂等键、不同请求体
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .header("Idempotency-Key", &idempotency_key)
+        .json(&json!({
+            "url": "https://www.example.com/other",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,165 @@
+use std::{env, time::Duration};
+use reqwest::{Client, StatusCode};
+use serde_json::{json, Value};
+use tokio_shortlink::handlers::shortlink::{BatchErrorCode, ImportReport, ImportStatus};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_import_and_export_links() {
+    // 小批量同步导入并返回逐行报告，按筛选条件导出 CSV / NDJSON
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let shortlink_min_ttl = env::var("SHORTLINK_MIN_TTL")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(60);
+    let login_url = format!("http://{}/login", addr);
+    let import_url = format!("http://{}/links/import", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let csv = format!(
+        "long_url,short_code,ttl,title\n\
+         https://www.example.com/import/1,import1,{ttl},Imported one\n\
+         https://www.example.com/import/2,import2,,\n\
+         not a url,import3,,\n\
+         https://www.example.com/import/4,import4,soon,\n",
+        ttl = shortlink_min_ttl,
+    );
+    let res = client
+        .post(&import_url)
+        .bearer_auth(&token)
+        .header("Content-Type", "text/csv")
+        .body(csv)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let report = res.json::<ImportReport>().await.unwrap();
+    assert_eq!(report.status, ImportStatus::Done);
+    assert_eq!(report.total, 4);
+    assert_eq!(report.succeeded, 2);
+    assert_eq!(report.failed, 2);
+    assert!(report.results[0].short_url.as_deref().unwrap().ends_with("/s/import1"));
+    assert_eq!(report.results[2].error.as_ref().unwrap().code, BatchErrorCode::InvalidUrl);
+    assert_eq!(report.results[3].error.as_ref().unwrap().code, BatchErrorCode::InvalidRow);
+
+    // 缺少 long_url 列
+    let res = client
+        .post(&import_url)
+        .bearer_auth(&token)
+        .body("url\nhttps://www.example.com\n")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 导出 CSV：表头 + 匹配的两行
+    let res = client
+        .get(format!("http://{}/links/export?format=csv&short_code=import", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers()["content-type"].to_str().unwrap().starts_with("text/csv"));
+    let body = res.text().await.unwrap();
+    let lines: Vec<&str> = body.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("id,short_code,"));
+    assert!(lines[1..].iter().any(|l| l.contains("import1") && l.contains("Imported one")));
+
+    // 导出 NDJSON
+    let body = client
+        .get(format!("http://{}/links/export?format=ndjson&short_code=import", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let links: Vec<Value> = body
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(links.len(), 2);
+    assert!(links.iter().any(|l| l["short_code"] == "import2"));
+}
+
+
+#[tokio::test]
+async fn test_import_background_job() {
+    // 超过同步行数上限时转为后台任务，通过任务编号查询结果
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let import_sync_max_rows = env::var("IMPORT_SYNC_MAX_ROWS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(100);
+    let login_url = format!("http://{}/login", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let rows = import_sync_max_rows + 1;
+    let mut csv = String::from("long_url,title\n");
+    for i in 0..rows {
+        csv.push_str(&format!("https://www.example.com/bulk/{},Bulk {}\n", i, i));
+    }
+    let res = client
+        .post(format!("http://{}/links/import", addr))
+        .bearer_auth(&token)
+        .body(csv)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::ACCEPTED);
+    let report = res.json::<ImportReport>().await.unwrap();
+    assert_eq!(report.total, rows);
+    let status_url = format!("http://{}/links/import/{}", addr, report.job_id.unwrap());
+
+    let mut report = None;
+    for _ in 0..50 {
+        let current = client
+            .get(&status_url)
+            .bearer_auth(&token)
+            .send()
+            .await
+            .unwrap()
+            .json::<ImportReport>()
+            .await
+            .unwrap();
+        if current.status == ImportStatus::Done {
+            report = Some(current);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    let report = report.expect("import job not finished");
+    assert_eq!(report.succeeded, rows);
+    assert_eq!(report.results.len(), rows);
+
+    // 其他用户查不到该任务
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token1 = common::login(&login_url, &login_body).await;
+    let res = client
+        .get(&status_url)
+        .bearer_auth(&token1)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
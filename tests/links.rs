@@ -42,7 +42,7 @@ async fn test_list_links() {
     assert_eq!(res.status(), StatusCode::OK);
     let links = res.json::<LinkList>().await.unwrap();
     assert_eq!(links.links.len(), 2);
-    assert_eq!(links.count, 2);
+    assert_eq!(links.count, Some(2));
 
     // 带参数
     let res = client
@@ -57,5 +57,93 @@ async fn test_list_links() {
     assert_eq!(res.status(), StatusCode::OK);
     let links = res.json::<LinkList>().await.unwrap();
     assert_eq!(links.links.len(), 1);
-    assert_eq!(links.count, 1);
+    assert_eq!(links.count, Some(1));
+}
+
+
+#[tokio::test]
+async fn test_list_links_cursor() {
+    // 游标翻页：按短码升序，每页 2 条；翻页时缺省不统计总数
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let list_url = format!("http://{}/links", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    for code in ["page2", "page0", "page1"] {
+        let shorten_body = json!({
+            "url": format!("https://www.example.com/{}", code),
+            "short_code": code,
+        });
+        common::shorten(&shorten_url, &shorten_body, &token).await;
+    }
+
+    let links = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .query(&[("short_code", "page"), ("sort", "short_code"), ("order", "asc"), ("limit", "2")])
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let codes: Vec<&str> = links.links.iter().map(|l| l.short_code.as_str()).collect();
+    assert_eq!(codes, ["page0", "page1"]);
+    assert_eq!(links.count, Some(3));
+    let cursor = links.next_cursor.unwrap();
+
+    let links = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .query(&[("short_code", "page"), ("sort", "short_code"), ("order", "asc"), ("limit", "2"), ("cursor", cursor.as_str())])
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert_eq!(links.links.len(), 1);
+    assert_eq!(links.links[0].short_code, "page2");
+    assert_eq!(links.count, None);
+    assert!(links.next_cursor.is_none());
+
+    // 降序
+    let links = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .query(&[("short_code", "page"), ("sort", "short_code"), ("order", "desc"), ("limit", "1")])
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert_eq!(links.links[0].short_code, "page2");
+
+    // 游标与排序方式不一致
+    let res = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .query(&[("short_code", "page"), ("sort", "click_count"), ("cursor", cursor.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 游标不合法
+    let res = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .query(&[("cursor", "garbage")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 }
@@ -0,0 +1,75 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy};
+use serde_json::json;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_max_clicks_link() {
+    // 访问达到上限后返回 410
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/burn2", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/burn",
+        "short_code": "burn2",
+        "max_clicks": 2,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    for _ in 0..2 {
+        let res = client
+            .get(&redirect_url)
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_redirection());
+    }
+
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::GONE);
+}
+
+
+#[tokio::test]
+async fn test_max_clicks_invalid() {
+    // max_clicks 必须大于 0
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/burn",
+            "max_clicks": 0,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
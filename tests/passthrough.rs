@@ -0,0 +1,66 @@
+use std::env;
+use reqwest::{Client, redirect::Policy, header};
+use serde_json::json;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_query_passthrough_and_utm() {
+    // 开启透传的短链合并访问参数并追加 UTM 模板，未开启的短链忽略访问参数
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/landing?ref=home",
+        "short_code": "pass1",
+        "forward_query": true,
+        "utm": {
+            "source": "newsletter",
+            "campaign": "spring sale",
+        },
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/landing?ref=home",
+        "short_code": "pass2",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let cases = [
+        (
+            format!("http://{}/s/pass1?utm_source=x&q=%E4%BD%A0%20%26", addr),
+            "https://www.example.com/landing?ref=home&utm_campaign=spring+sale&utm_source=x&q=%E4%BD%A0+%26",
+        ),
+        (
+            format!("http://{}/s/pass1", addr),
+            "https://www.example.com/landing?ref=home&utm_source=newsletter&utm_campaign=spring+sale",
+        ),
+        (
+            format!("http://{}/s/pass2?utm_source=x", addr),
+            "https://www.example.com/landing?ref=home",
+        ),
+    ];
+    // 第二轮走 Redis 缓存
+    for _ in 0..2 {
+        for (url, expected) in &cases {
+            let res = client
+                .get(url)
+                .header("User-Agent", "test")
+                .send()
+                .await
+                .unwrap();
+            assert!(res.status().is_redirection());
+            assert_eq!(res.headers()[header::LOCATION], *expected);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy, header};
+use serde_json::json;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_password_protected_link() {
+    // 未输入密码返回表单，密码错误返回 401，密码正确跳转
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/secret1", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/private",
+        "short_code": "secret1",
+        "password": "open-sesame",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    // 未输入密码：返回 HTML 表单
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(res.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/html"));
+    assert!(res.text().await.unwrap().contains(r#"name="password""#));
+
+    // 密码错误
+    let res = client
+        .post(&redirect_url)
+        .header("User-Agent", "test")
+        .form(&[("password", "wrong")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // 密码正确
+    let res = client
+        .post(&redirect_url)
+        .header("User-Agent", "test")
+        .form(&[("password", "open-sesame")])
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_redirection());
+    assert_eq!(res.headers()[header::LOCATION], "https://www.example.com/private");
+}
+
+
+#[tokio::test]
+async fn test_password_too_short() {
+    // 密码长度校验
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/private",
+            "password": "abc",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,75 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::LinkList;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_pause_and_resume_link() {
+    // 暂停后返回 410，恢复后正常跳转
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/pause0", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/pause",
+        "short_code": "pause0",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let links = client
+        .get(format!("http://{}/links?short_code=pause0", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link_id = links.links[0].id;
+
+    // 暂停
+    let res = client
+        .post(format!("http://{}/links/{}/pause", addr, link_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::GONE);
+
+    // 恢复
+    let res = client
+        .post(format!("http://{}/links/{}/resume", addr, link_id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(&redirect_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_redirection());
+    assert_eq!(res.headers()["location"], "https://www.example.com/pause");
+}
@@ -0,0 +1,147 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy};
+use serde_json::json;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_preview_page() {
+    // 预览页展示目标地址；设置了访问密码的短链不展示
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/preview",
+        "short_code": "prev1",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/secret",
+        "short_code": "prev2",
+        "password": "open-sesame",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    for url in [
+        format!("http://{}/p/prev1", addr),
+        format!("http://{}/s/prev1+", addr),
+    ] {
+        let res = client
+            .get(&url)
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let page = res.text().await.unwrap();
+        assert!(page.contains("https://www.example.com/preview"));
+        assert!(page.contains(r#"href="/s/prev1""#));
+    }
+
+    let res = client
+        .get(format!("http://{}/p/prev2", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert!(!res.text().await.unwrap().contains("https://www.example.com/secret"));
+
+    let res = client
+        .get(format!("http://{}/p/no-such-code", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+
+#[tokio::test]
+async fn test_forced_interstitial() {
+    // 开启中间页的短链访问时不直接跳转，确认“继续访问”后才计点击并跳转
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/inter1", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://untrusted.example.net/download",
+        "short_code": "inter1",
+        "interstitial": true,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    // 第二次走 Redis 缓存
+    for _ in 0..2 {
+        let res = client
+            .get(&redirect_url)
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let page = res.text().await.unwrap();
+        assert!(page.contains("https://untrusted.example.net/download"));
+        assert!(page.contains(r#"href="/s/inter1/continue""#));
+    }
+
+    let res = client
+        .get(format!("http://{}/s/inter1/continue", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_redirection());
+    assert_eq!(res.headers()["location"], "https://untrusted.example.net/download");
+
+    // 只展示中间页不消耗访问次数
+    let shorten_body = json!({
+        "url": "https://untrusted.example.net/once",
+        "short_code": "inter2",
+        "interstitial": true,
+        "max_clicks": 1,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    for _ in 0..3 {
+        let res = client
+            .get(format!("http://{}/s/inter2", addr))
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    let continue_url = format!("http://{}/s/inter2/continue", addr);
+    let res = client
+        .get(&continue_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_redirection());
+    let res = client
+        .get(&continue_url)
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::GONE);
+}
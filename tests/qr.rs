@@ -0,0 +1,141 @@
+use std::env;
+use chrono::{Duration, Utc};
+use reqwest::{Client, StatusCode, header};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::LinkList;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_link_qr() {
+    // 所有者通过受保护接口获取二维码；公开二维码需短链开启 qr_public
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/print",
+        "short_code": "qr1",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    let shorten_body = json!({
+        "url": "https://www.example.com/print",
+        "short_code": "qr2",
+        "qr_public": true,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let links = client
+        .get(format!("http://{}/links?short_code=qr1", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let qr_url = format!("http://{}/links/{}/qr", addr, links.links[0].id);
+
+    // 默认 SVG
+    let res = client.get(&qr_url).bearer_auth(&token).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/svg+xml");
+    assert!(res.text().await.unwrap().contains("<svg"));
+
+    let res = client
+        .get(&qr_url)
+        .bearer_auth(&token)
+        .query(&[("format", "png"), ("size", "512"), ("margin", "2")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/png");
+    assert!(res.bytes().await.unwrap().starts_with(b"\x89PNG"));
+
+    // 参数越界
+    let res = client
+        .get(&qr_url)
+        .bearer_auth(&token)
+        .query(&[("size", "10")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 未登录
+    let res = client.get(&qr_url).send().await.unwrap();
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    // 公开二维码
+    let res = client
+        .get(format!("http://{}/s/qr1.qr", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = client
+        .get(format!("http://{}/s/qr2.qr?format=png", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "image/png");
+    // 与跳转一致：未生效返回 403，已暂停返回 410
+    let activate_at = Utc::now() + Duration::hours(1);
+    let shorten_body = json!({
+        "url": "https://www.example.com/print",
+        "short_code": "qr3",
+        "qr_public": true,
+        "activate_at": activate_at.to_rfc3339(),
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    let res = client
+        .get(format!("http://{}/s/qr3.qr", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/print",
+        "short_code": "qr4",
+        "qr_public": true,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    let links = client
+        .get(format!("http://{}/links?short_code=qr4", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let res = client
+        .post(format!("http://{}/links/{}/pause", addr, links.links[0].id))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(format!("http://{}/s/qr4.qr", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::GONE);
+}
@@ -0,0 +1,96 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy, header};
+use serde_json::{json, Value};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_redirect_type() {
+    // 按短链配置返回跳转状态码，永久跳转允许缓存并在创建时给出提示
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let cases = [
+        ("rt301", 301, StatusCode::MOVED_PERMANENTLY, true),
+        ("rt302", 302, StatusCode::FOUND, false),
+        ("rt307", 307, StatusCode::TEMPORARY_REDIRECT, false),
+        ("rt308", 308, StatusCode::PERMANENT_REDIRECT, true),
+    ];
+    for (code, redirect_type, status, permanent) in cases {
+        let res = client
+            .post(&shorten_url)
+            .bearer_auth(&token)
+            .json(&json!({
+                "url": "https://www.example.com/seo",
+                "short_code": code,
+                "redirect_type": redirect_type,
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = res.json::<Value>().await.unwrap();
+        assert_eq!(body["warning"].is_string(), permanent);
+
+        // 第二次走 Redis 缓存
+        for _ in 0..2 {
+            let res = client
+                .get(format!("http://{}/s/{}", addr, code))
+                .header("User-Agent", "test")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status);
+            assert_eq!(res.headers()[header::LOCATION], "https://www.example.com/seo");
+            let cache_control = res.headers()[header::CACHE_CONTROL].to_str().unwrap();
+            assert_eq!(cache_control.contains("max-age"), permanent);
+            // 只允许访问者自己的浏览器缓存
+            assert!(cache_control.starts_with("private"));
+        }
+    }
+
+    // 限制访问次数的永久跳转不允许缓存，否则缓存期内的访问不消耗额度
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/seo",
+            "short_code": "rt301max",
+            "redirect_type": 301,
+            "max_clicks": 5,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = client
+        .get(format!("http://{}/s/rt301max", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::MOVED_PERMANENTLY);
+    assert_eq!(res.headers()[header::CACHE_CONTROL], "private, no-store");
+
+    // 不支持的状态码
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/seo",
+            "redirect_type": 304,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(res.status().is_client_error());
+}
@@ -0,0 +1,87 @@
+use std::env;
+use chrono::Utc;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::{LinkList, LinkRenewResp};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_renew_link() {
+    // 续期：在当前过期时间基础上延长，续期后剩余有效时间不能超过最大有效时间
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let shortlink_min_ttl = env::var("SHORTLINK_MIN_TTL")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(60);
+    let shortlink_max_ttl = env::var("SHORTLINK_MAX_TTL")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(3600);
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/renew",
+        "short_code": "renew1",
+        "ttl": shortlink_min_ttl,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let links = client
+        .get(format!("http://{}/links?short_code=renew1", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let renew_url = format!("http://{}/links/{}/renew", addr, links.links[0].id);
+
+    // 缺省延长最小有效时间
+    let res = client
+        .post(&renew_url)
+        .bearer_auth(&token)
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let expire_at = res.json::<LinkRenewResp>().await.unwrap().expire_at;
+    let remaining = (expire_at - Utc::now()).num_seconds();
+    assert!(remaining > 2 * shortlink_min_ttl - 60 && remaining <= 2 * shortlink_min_ttl);
+
+    // 超过最大有效时间
+    let res = client
+        .post(&renew_url)
+        .bearer_auth(&token)
+        .json(&json!({ "ttl": shortlink_max_ttl }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 非本人短链
+    let login_body2 = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token2 = common::login(&login_url, &login_body2).await;
+    let res = client
+        .post(&renew_url)
+        .bearer_auth(&token2)
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,109 @@
+use std::env;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::{LinkList, TagStats};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_link_tags() {
+    // 创建时打标签，按标签筛选，编辑时替换 / 清除标签，按标签汇总
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    // 标签去掉首尾空白并按不区分大小写去重
+    let shorten_body = json!({
+        "url": "https://www.example.com/spring",
+        "short_code": "tag1",
+        "tags": [" spring-sale ", "Spring-Sale", "newsletter"],
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    let shorten_body = json!({
+        "url": "https://www.example.com/spring/2",
+        "short_code": "tag2",
+        "tags": ["spring-sale"],
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let links = client
+        .get(format!("http://{}/links?tag=spring-sale&limit=100", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert_eq!(links.count, Some(2));
+    let tag1 = links.links.iter().find(|l| l.short_code == "tag1").unwrap();
+    assert_eq!(tag1.tags, vec!["newsletter".to_string(), "spring-sale".to_string()]);
+
+    // 标签不合法
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/spring",
+            "tags": ["   "],
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 汇总
+    let stats = client
+        .get(format!("http://{}/tags/stats", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<Vec<TagStats>>()
+        .await
+        .unwrap();
+    let spring = stats.iter().find(|s| s.tag == "spring-sale").unwrap();
+    assert_eq!(spring.links, 2);
+    assert_eq!(spring.clicks, 0);
+
+    // 编辑：传空数组清除标签
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, tag1.id))
+        .bearer_auth(&token)
+        .json(&json!({ "tags": [] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let links = client
+        .get(format!("http://{}/links?tag=spring-sale&limit=100", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert_eq!(links.count, Some(1));
+    assert_eq!(links.links[0].short_code, "tag2");
+
+    let links = client
+        .get(format!("http://{}/links?short_code=tag1", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert!(links.links[0].tags.is_empty());
+}
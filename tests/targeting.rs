@@ -0,0 +1,56 @@
+use std::env;
+use reqwest::{Client, redirect::Policy, header};
+use serde_json::json;
+
+mod common;
+
+
+const IPHONE_UA: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+const ANDROID_UA: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Mobile Safari/537.36";
+const DESKTOP_UA: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0 Safari/537.36";
+
+
+#[tokio::test]
+async fn test_platform_targeting() {
+    // 按 User-Agent 跳转到对应平台地址，未配置的平台跳转默认长链
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/target1", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/app",
+        "short_code": "target1",
+        "targets": {
+            "ios": "https://apps.apple.com/app/id123",
+            "android": "https://play.google.com/store/apps/details?id=com.example",
+        },
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let cases = [
+        (IPHONE_UA, "https://apps.apple.com/app/id123"),
+        (ANDROID_UA, "https://play.google.com/store/apps/details?id=com.example"),
+        (DESKTOP_UA, "https://www.example.com/app"),
+    ];
+    // 第二轮走 Redis 缓存
+    for _ in 0..2 {
+        for (ua, expected) in cases {
+            let res = client
+                .get(&redirect_url)
+                .header("User-Agent", ua)
+                .send()
+                .await
+                .unwrap();
+            assert!(res.status().is_redirection());
+            assert_eq!(res.headers()[header::LOCATION], expected);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::env;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::LinkList;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_link_title_note() {
+    // 创建时填写标题和备注，编辑时修改 / 清除
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/launch",
+        "short_code": "note1",
+        "title": "Launch page",
+        "note": "Shared in the October newsletter",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let list_url = format!("http://{}/links?short_code=note1", addr);
+    let links = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link = &links.links[0];
+    assert_eq!(link.title.as_deref(), Some("Launch page"));
+    assert_eq!(link.note.as_deref(), Some("Shared in the October newsletter"));
+    let link_id = link.id;
+
+    // 修改标题，清除备注
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&json!({
+            "title": "Launch page v2",
+            "note": null,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let links = client
+        .get(&list_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    assert_eq!(links.links[0].title.as_deref(), Some("Launch page v2"));
+    assert_eq!(links.links[0].note, None);
+
+    // 标题过长
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&json!({ "title": "x".repeat(201) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,145 @@
+use reqwest::{Client, StatusCode};
+use std::env;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::LinkList;
+
+mod common;
+
+
+#[tokio::test]
+async fn test_trash_and_restore() {
+    // 测试删除进入回收站，恢复后重新可见
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap();
+
+    // 登录获取 token（专用账号，恢复的短链不影响其它用例的列表计数）
+    let login_url = format!("http://{}/login", addr);
+    let login_body = json!({
+        "email": "test4@example.com",
+        "password": "password4",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    // 创建短链（短码唯一，避免与历史数据冲突）
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let short_code = format!("trash_{}", nanos % 1_000_000_000);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let shorten_body = json!({
+        "url": "https://www.example.com",
+        "short_code": short_code,
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let links_url = format!("http://{}/links?short_code={}", addr, short_code);
+    let res = client
+        .get(&links_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    let links = res.json::<LinkList>().await.unwrap();
+    assert_eq!(links.count, Some(1));
+    let link_id = links.links[0].id;
+
+    // 删除短链
+    let delete_url = format!("http://{}/delete", addr);
+    let res = client
+        .post(&delete_url)
+        .bearer_auth(&token)
+        .json(&json!({ "ids": [link_id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // 删除后不再出现在列表中，也无法访问
+    let res = client
+        .get(&links_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    let links = res.json::<LinkList>().await.unwrap();
+    assert_eq!(links.count, Some(0));
+
+    let res = Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .get(format!("http://{}/s/{}", addr, short_code))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // 回收站中的短码在清理前不可复用，与已存在的自定义短码一致返回 400
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&shorten_body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 回收站列表
+    let trash_url = format!("http://{}/links/trash?limit=100", addr);
+    let res = client
+        .get(&trash_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let trash = res.json::<LinkList>().await.unwrap();
+    let item = trash.links.iter().find(|l| l.id == link_id).unwrap();
+    assert!(item.deleted_at.is_some());
+
+    // 恢复
+    let restore_url = format!("http://{}/links/restore", addr);
+    let res = client
+        .post(&restore_url)
+        .bearer_auth(&token)
+        .json(&json!({ "ids": [link_id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(&links_url)
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    let links = res.json::<LinkList>().await.unwrap();
+    assert_eq!(links.count, Some(1));
+    assert!(links.links[0].deleted_at.is_none());
+}
+
+
+#[tokio::test]
+async fn test_restore_invalid() {
+    // 测试恢复参数校验
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap();
+
+    let login_url = format!("http://{}/login", addr);
+    let login_body = json!({
+        "email": "test3@example.com",
+        "password": "password3",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let restore_url = format!("http://{}/links/restore", addr);
+    let res = client
+        .post(&restore_url)
+        .bearer_auth(&token)
+        .json(&json!({ "ids": [] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
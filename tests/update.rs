@@ -0,0 +1,127 @@
+use std::{env, time::Duration};
+use reqwest::{Client, StatusCode, redirect::Policy};
+use serde_json::json;
+use sqlx::MySqlPool;
+use tokio_shortlink::{
+    handlers::shortlink::LinkList,
+    models::{db, link::Link},
+};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_update_link() {
+    // 编辑短链：修改目标地址和短码，跳转立即生效
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    // 创建短链
+    let shorten_body = json!({
+        "url": "https://www.example.com/before",
+        "short_code": "edit0",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    // 访问一次，写入缓存
+    let res = client
+        .get(format!("http://{}/s/edit0", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["location"], "https://www.example.com/before");
+    // 等待后台任务把本次访问写入 Redis Stream
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let links = client
+        .get(format!("http://{}/links?short_code=edit0", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link_id = links.links[0].id;
+
+    // 修改目标地址和短码
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/after",
+            "short_code": "edit1",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // 只提交与当前相同的短码，视为无变化
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&json!({ "short_code": "edit1" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get(format!("http://{}/s/edit1", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["location"], "https://www.example.com/after");
+
+    // 改名前尚未同步的访问日志同步后归到新短码
+    let db_url = env::var("DATABASE_URL").expect("DATABASE_URL not found");
+    let pool = MySqlPool::connect(&db_url).await.expect("connect to db");
+    let redis_url = env::var("REDIS_URL").expect("REDIS_URL not found");
+    let redis_pool = db::new_redis_pool(&redis_url, 1, 1000, 1000, 1000).expect("create redis pool");
+    let mut conn = redis_pool.get().await.expect("redis connection");
+    Link::sync_visit_logs(&pool, &mut conn, 100).await.unwrap();
+    let old_visits = sqlx::query_scalar!("SELECT COUNT(*) FROM visit_logs WHERE short_code = 'edit0'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let new_visits = sqlx::query_scalar!("SELECT COUNT(*) FROM visit_logs WHERE short_code = 'edit1'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(old_visits, 0);
+    assert!(new_visits >= 1);
+
+    let res = client
+        .get(format!("http://{}/s/edit0", addr))
+        .header("User-Agent", "test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // 非本人短链
+    let login_body2 = json!({
+        "email": "test0@example.com",
+        "password": "password0",
+    });
+    let token2 = common::login(&login_url, &login_body2).await;
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token2)
+        .json(&json!({ "url": "https://www.example.com/hijack" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,97 @@
+use std::env;
+use reqwest::{Client, StatusCode};
+use serde_json::json;
+use tokio_shortlink::handlers::shortlink::{BatchCreateResp, BatchErrorCode, LinkList};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_destination_policy() {
+    // 危险协议、内网地址和指向本服务的地址在创建、批量创建和编辑时都被拒绝
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let batch_url = format!("http://{}/shorten/batch", addr);
+
+    let login_body = json!({
+        "email": "test1@example.com",
+        "password": "password1",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let rejected = [
+        "javascript:alert(1)".to_string(),
+        "data:text/html,hello".to_string(),
+        "file:///etc/passwd".to_string(),
+        "http://192.168.1.1/admin".to_string(),
+        "http://[::1]/".to_string(),
+        format!("http://{}/s/loop", addr),
+    ];
+    for url in &rejected {
+        let res = client
+            .post(&shorten_url)
+            .bearer_auth(&token)
+            .json(&json!({ "url": url }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST, "{}", url);
+    }
+
+    // 跳转规则中的地址同样校验
+    let res = client
+        .post(&shorten_url)
+        .bearer_auth(&token)
+        .json(&json!({
+            "url": "https://www.example.com/policy",
+            "targets": { "android": "http://10.0.0.1/app.apk" },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // 批量创建：逐条返回错误
+    let res = client
+        .post(&batch_url)
+        .bearer_auth(&token)
+        .json(&json!([
+            { "url": "https://www.example.com/policy0" },
+            { "url": "javascript:alert(1)" },
+        ]))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let resp = res.json::<BatchCreateResp>().await.unwrap();
+    assert!(resp.results[0].short_url.is_some());
+    assert_eq!(resp.results[1].error.as_ref().unwrap().code, BatchErrorCode::UrlNotAllowed);
+
+    // 编辑
+    let shorten_body = json!({
+        "url": "https://www.example.com/policy",
+        "short_code": "policy1",
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+    let links = client
+        .get(format!("http://{}/links?short_code=policy1", addr))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap()
+        .json::<LinkList>()
+        .await
+        .unwrap();
+    let link_id = links.links[0].id;
+
+    let res = client
+        .patch(format!("http://{}/links/{}", addr, link_id))
+        .bearer_auth(&token)
+        .json(&json!({ "url": "http://127.0.0.1:6379/" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,101 @@
+use std::env;
+use reqwest::{Client, StatusCode, redirect::Policy, header};
+use serde_json::{json, Value};
+
+mod common;
+
+
+#[tokio::test]
+async fn test_weighted_variants() {
+    // 按权重分流：每次跳转都落在某个分流目标上，统计接口按目标返回
+    let client = Client::builder().redirect(Policy::none()).build().unwrap();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+    let redirect_url = format!("http://{}/s/ab1", addr);
+    let stats_url = format!("http://{}/stats/variants", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let shorten_body = json!({
+        "url": "https://www.example.com/ab",
+        "short_code": "ab1",
+        "variants": [
+            { "url": "https://a.example.com/landing", "weight": 70 },
+            { "url": "https://b.example.com/landing", "weight": 30 },
+        ],
+    });
+    common::shorten(&shorten_url, &shorten_body, &token).await;
+
+    let expected = ["https://a.example.com/landing", "https://b.example.com/landing"];
+    for _ in 0..10 {
+        let res = client
+            .get(&redirect_url)
+            .header("User-Agent", "test")
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_redirection());
+        let location = res.headers()[header::LOCATION].to_str().unwrap();
+        assert!(expected.contains(&location), "unexpected location: {}", location);
+    }
+
+    let res = client
+        .get(&stats_url)
+        .bearer_auth(&token)
+        .query(&json!({ "short_code": "ab1", "days": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let stats = res.json::<Vec<Value>>().await.unwrap();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0]["url"], expected[0]);
+    assert_eq!(stats[0]["weight"], 70);
+    assert_eq!(stats[1]["url"], expected[1]);
+    assert_eq!(stats[1]["weight"], 30);
+}
+
+
+#[tokio::test]
+async fn test_invalid_variants() {
+    // 分流目标少于 2 个或权重为 0 时拒绝创建
+    let client = Client::new();
+    let addr = env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".into());
+    let login_url = format!("http://{}/login", addr);
+    let shorten_url = format!("http://{}/shorten", addr);
+
+    let login_body = json!({
+        "email": "test2@example.com",
+        "password": "password2",
+    });
+    let token = common::login(&login_url, &login_body).await;
+
+    let bodies = [
+        json!({
+            "url": "https://www.example.com/ab",
+            "variants": [{ "url": "https://a.example.com", "weight": 1 }],
+        }),
+        json!({
+            "url": "https://www.example.com/ab",
+            "variants": [
+                { "url": "https://a.example.com", "weight": 0 },
+                { "url": "https://b.example.com", "weight": 1 },
+            ],
+        }),
+    ];
+    for body in bodies {
+        let res = client
+            .post(&shorten_url)
+            .bearer_auth(&token)
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+}